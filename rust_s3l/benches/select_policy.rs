@@ -0,0 +1,122 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rust_s3l::{
+  get_l2_n_dist, sub_vectors_into, sum_vectors_in_place, vector_scalar_multiply_in_place, Euclidean, Metric, Objective,
+  S3LAgent, TwoPeak, XiEntry
+};
+
+// The by-value versions the agent used before the slice rewrite, kept as a baseline.
+fn legacy_sub_vectors(dims: u32, a: Vec<f64>, b: Vec<f64>) -> Vec<f64> {
+  let d_range: Vec<usize> = (0..dims)
+  .map(|x: u32| -> usize {x as usize})
+  .collect::<Vec<usize>>();
+  d_range
+  .iter()
+  .map(|x: &usize| -> f64 {b[*x] - a[*x]})
+  .collect::<Vec<f64>>()
+}
+
+fn legacy_vector_scalar_multiply(m_vector: Vec<f64>, m_scalar: f64) -> Vec<f64> {
+  m_vector
+  .iter()
+  .map(|x| {x * m_scalar})
+  .collect::<Vec<f64>>()
+}
+
+fn legacy_sum_vectors(dims: u32, a: Vec<f64>, b: Vec<f64>) -> Vec<f64> {
+  let d_range: Vec<usize> = (0..dims)
+  .map(|x: u32| -> usize {x as usize})
+  .collect::<Vec<usize>>();
+  d_range
+  .iter()
+  .map(|x: &usize| -> f64 {a[*x] + b[*x]})
+  .collect::<Vec<f64>>()
+}
+
+fn legacy_get_l2_n_dist(a: Vec<f64>, b: Vec<f64>) -> f64 {
+  let dist_sum: f64 = a
+  .iter()
+  .zip(b.iter())
+  .collect::<Vec<(&f64,&f64)>>()
+  .iter()
+  .fold(0_f64, |a, x| {a + ((x.0 - x.1).powf(2_f64))});
+  dist_sum.sqrt()
+}
+
+fn vector_math(c: &mut Criterion) {
+  let dims: u32 = 16;
+  let a: Vec<f64> = (0..dims).map(|x| {x as f64 / 16_f64}).collect::<Vec<f64>>();
+  let b: Vec<f64> = (0..dims).map(|x| {1_f64 - x as f64 / 16_f64}).collect::<Vec<f64>>();
+  let mut group = c.benchmark_group("move_towards");
+  group.bench_function("legacy", |bencher| {
+    bencher.iter(|| {
+      let delta: Vec<f64> = legacy_sub_vectors(dims, a.clone(), b.clone());
+      let delta: Vec<f64> = legacy_vector_scalar_multiply(delta, black_box(0.25));
+      let moved: Vec<f64> = legacy_sum_vectors(dims, a.clone(), delta);
+      legacy_get_l2_n_dist(moved, b.clone())
+    })
+  });
+  group.bench_function("in_place", |bencher| {
+    let mut moved: Vec<f64> = a.clone();
+    let mut delta: Vec<f64> = vec![0_f64; dims as usize];
+    bencher.iter(|| {
+      moved.copy_from_slice(&a);
+      sub_vectors_into(&moved, &b, &mut delta);
+      vector_scalar_multiply_in_place(&mut delta, black_box(0.25));
+      sum_vectors_in_place(&mut moved, &delta);
+      get_l2_n_dist(&moved, &b)
+    })
+  });
+  group.finish();
+}
+
+// An agent that has run on TwoPeak for `entries` evaluations, so that most of its table is near the maximum.
+fn converged_agent(entries: usize) -> S3LAgent {
+  let mut agentmodel: S3LAgent = S3LAgent::builder(2, 3.1).seed(1).build();
+  for _i in 0..entries {
+    let policy: Vec<f64> = agentmodel.select_policy();
+    let performance: f64 = TwoPeak.evaluate(&agentmodel.decode(&policy));
+    agentmodel.update_xi_table(policy, performance);
+  }
+  agentmodel
+}
+
+fn select_policy(c: &mut Criterion) {
+  let mut group = c.benchmark_group("select_policy");
+  for entries in [100_usize, 1000, 10000] {
+    let mut agentmodel: S3LAgent = converged_agent(entries);
+    group.bench_function(format!("{}_entries", entries), |bencher| {
+      bencher.iter(|| {agentmodel.select_policy()})
+    });
+  }
+  group.finish();
+}
+
+// The nearest of the few low-utility entries of a converged table, the query avoidance makes for minima.
+fn nearest_filtered(c: &mut Criterion) {
+  let mut group = c.benchmark_group("nearest_filtered");
+  let target: Vec<f64> = vec![0.3, 0.6];
+  for entries in [1000_usize, 16000] {
+    let agentmodel: S3LAgent = converged_agent(entries);
+    let is_min = |x: &XiEntry| -> bool {agentmodel.xi_table().score(x).is_none_or(|y| {agentmodel.utility(y) < 0.75})};
+    let mins: Vec<&XiEntry> = agentmodel
+    .xi_table()
+    .iter()
+    .filter(|x| {is_min(x)})
+    .collect::<Vec<&XiEntry>>();
+    group.bench_function(format!("tree_{}_entries", entries), |bencher| {
+      bencher.iter(|| {agentmodel.xi_table().nearest(black_box(&target), &Euclidean, is_min).map(|x| {x.1})})
+    });
+    group.bench_function(format!("scan_{}_entries", entries), |bencher| {
+      bencher.iter(|| {
+        mins
+        .iter()
+        .map(|x| {Euclidean.distance(&x.policy, black_box(&target))})
+        .min_by(|a, b| {a.total_cmp(b)})
+      })
+    });
+  }
+  group.finish();
+}
+
+criterion_group!(benches, vector_math, select_policy, nearest_filtered);
+criterion_main!(benches);
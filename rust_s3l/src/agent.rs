@@ -0,0 +1,668 @@
+use std::collections::BTreeMap;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::mip::MipEstimator;
+use crate::constraint::{Constraint, MAX_CONSTRAINT_RETRIES};
+use crate::noise::NoiseHandling;
+use crate::objective::ObjectiveSpec;
+use crate::observer::{Observer, QuietObserver};
+use crate::pareto::{ParetoRanking, ParetoSettings};
+use crate::persist::Checkpoint;
+use crate::performance::{Direction, Normalization};
+use crate::refine::{RefineState, Refinement};
+use crate::restart::Restart;
+use crate::schedule::Schedule;
+use crate::space::SearchSpace;
+use crate::metric::{check_metric_dims, Euclidean, Metric};
+use crate::warm_up::{Sampling, WarmUp, MAX_SOBOL_DIMS};
+use crate::vector::{sum_vectors_in_place, vector_scalar_multiply_in_place};
+use crate::xi_table::{XiEntry, XiTable};
+
+/// How many times `generate_avoidance_point` redraws a point before settling for the last one.
+pub const MAX_AVOIDANCE_RETRIES: u32 = 100;
+
+/// The closest a proposal is pulled toward the probabilistic max while trials are pending, in the metric's distance
+/// between normalized policies. Keeps the trials of a batch from all landing on the same point.
+pub const MIN_PENDING_SEPARATION: f64 = 0.05;
+
+/// A class of `count` entries is scanned when `count^2` is below this many times the table's size: a tree walk
+/// filtered down to the class visits about `size / count` other entries for each of its own.
+const SCAN_FACTOR: usize = 64;
+
+/// Pulls weaker than this are dropped altogether when halving them to keep clear of pending trials.
+const MIN_PENDING_PULL: f64 = 1e-3;
+
+/// Marker returned by steps that only mutate state.
+pub struct Done;
+
+/// An S3L agent over normalized policies in `[0, 1]^dims`, mapped to parameters through its [`SearchSpace`].
+///
+/// All randomness is drawn from the agent's own `rng`, so a seeded agent replays bit-for-bit.
+pub struct S3LAgent<R: Rng = StdRng> {
+  pub(crate) dims: u32,
+  pub(crate) mip: f64,
+  pub(crate) mip_estimator: Option<MipEstimator>,
+  pub(crate) normalization: Normalization,
+  pub(crate) noise: Option<NoiseHandling>,
+  pub(crate) pareto: Option<ParetoSettings>,
+  pub(crate) j: u32,
+  pub(crate) schedule: Schedule,
+  /// The exponent in use, `j` unless a schedule moves it.
+  pub(crate) exponent: f64,
+  /// Evaluations and new bests in the current success-rule window.
+  pub(crate) window_trials: usize,
+  pub(crate) window_successes: usize,
+  pub(crate) refinement: Option<Refinement>,
+  pub(crate) refine_state: RefineState,
+  pub(crate) restart: Option<Restart>,
+  pub(crate) restarts: u32,
+  /// Evaluations since the current epoch last improved.
+  pub(crate) stagnation: usize,
+  pub(crate) xi_table: XiTable,
+  pub(crate) upsilon: f64,
+  pub(crate) rng: R,
+  pub(crate) space: SearchSpace,
+  pub(crate) pending: BTreeMap<u64, Vec<f64>>,
+  pub(crate) next_trial_id: u64,
+  pub(crate) observer: Box<dyn Observer + Send>,
+  pub(crate) constraints: Vec<Box<dyn Constraint + Send>>,
+  pub(crate) metric: Box<dyn Metric + Send>,
+  pub(crate) checkpoint: Option<Checkpoint>,
+  pub(crate) objective: Option<ObjectiveSpec>,
+  pub(crate) warm_up: WarmUp,
+  /// How many initial design points have been drawn, and how many had been when the current epoch began.
+  pub(crate) design_drawn: u64,
+  pub(crate) design_start: u64,
+  pub(crate) latin_hypercube: Vec<Vec<f64>>,
+  pub(crate) scratch: Scratch
+}
+
+/// Buffers reused across proposals so that `select_policy` only allocates the policy it returns.
+#[derive(Default)]
+pub(crate) struct Scratch {
+  delta: Vec<f64>,
+  moved: Vec<f64>,
+  params: Vec<f64>,
+  stack: Vec<(usize, f64)>
+}
+
+/// Builder for [`S3LAgent`].
+pub struct S3LAgentBuilder {
+  dims: u32,
+  mip: f64,
+  mip_estimator: Option<MipEstimator>,
+  direction: Direction,
+  normalization: Option<Normalization>,
+  noise: Option<NoiseHandling>,
+  pareto: Option<ParetoSettings>,
+  j: u32,
+  schedule: Schedule,
+  refinement: Option<Refinement>,
+  restart: Option<Restart>,
+  seed: Option<u64>,
+  space: Option<SearchSpace>,
+  observer: Option<Box<dyn Observer + Send>>,
+  constraints: Vec<Box<dyn Constraint + Send>>,
+  metric: Option<Box<dyn Metric + Send>>,
+  checkpoint: Option<Checkpoint>,
+  objective: Option<ObjectiveSpec>,
+  warm_up: WarmUp
+}
+
+impl S3LAgentBuilder {
+  /// Starts a builder for an agent with `dims` dimensions and maximum ideal performance `mip`.
+  ///
+  /// When minimizing, `mip` is the ideal (lowest) performance instead.
+  pub fn new(dims: u32, mip: f64) -> S3LAgentBuilder {
+    S3LAgentBuilder {
+      dims,
+      mip,
+      mip_estimator: None,
+      direction: Direction::Maximize,
+      normalization: None,
+      noise: None,
+      pareto: None,
+      j: 2,
+      schedule: Schedule::Constant,
+      refinement: None,
+      restart: None,
+      seed: None,
+      space: None,
+      observer: None,
+      constraints: Vec::new(),
+      metric: None,
+      checkpoint: None,
+      objective: None,
+      warm_up: WarmUp::default(),
+    }
+  }
+
+  /// Sets the `j` exponent used for the upsilon boost and the step scaling. Defaults to 2.
+  pub fn j(mut self, j: u32) -> S3LAgentBuilder {
+    self.j = j;
+    self
+  }
+
+  /// Sets how the exponent moves away from `j` over the run. Defaults to [`Schedule::Constant`].
+  pub fn schedule(mut self, schedule: Schedule) -> S3LAgentBuilder {
+    self.schedule = schedule;
+    self
+  }
+
+  /// Enables local refinement around the incumbent, see [`Refinement`].
+  pub fn refinement(mut self, refinement: Refinement) -> S3LAgentBuilder {
+    self.refinement = Some(refinement);
+    self
+  }
+
+  /// Restarts the agent when it stagnates, see [`Restart`].
+  pub fn restart(mut self, restart: Restart) -> S3LAgentBuilder {
+    self.restart = Some(restart);
+    self
+  }
+
+  /// Replaces the mip given to [`S3LAgentBuilder::new`].
+  pub fn mip(mut self, mip: f64) -> S3LAgentBuilder {
+    self.mip = mip;
+    self
+  }
+
+  /// Re-estimates mip from the xi table before every proposal, using the builder's mip only until an estimate exists.
+  pub fn estimate_mip(mut self, estimator: MipEstimator) -> S3LAgentBuilder {
+    self.mip_estimator = Some(estimator);
+    self
+  }
+
+  /// Sets whether higher or lower performance is better. Defaults to [`Direction::Maximize`].
+  pub fn direction(mut self, direction: Direction) -> S3LAgentBuilder {
+    self.direction = direction;
+    self
+  }
+
+  /// Sets how performances are scaled for upsilon and avoidance. Defaults to [`Normalization::Ratio`] when
+  /// maximizing toward a positive mip and to [`Normalization::MinMax`] otherwise.
+  pub fn normalization(mut self, normalization: Normalization) -> S3LAgentBuilder {
+    self.normalization = Some(normalization);
+    self
+  }
+
+  /// Treats the objective as noisy: entries are ranked by a confidence bound and promising ones are re-evaluated.
+  pub fn noise(mut self, noise: NoiseHandling) -> S3LAgentBuilder {
+    self.noise = Some(noise);
+    self
+  }
+
+  /// Makes the agent multi-objective, see [`learning_S3L_pareto`](crate::learning_S3L_pareto).
+  pub fn pareto(mut self, pareto: ParetoSettings) -> S3LAgentBuilder {
+    self.pareto = Some(pareto);
+    self
+  }
+
+  /// Seeds the agent's random stream. Without a seed the stream is seeded from system entropy.
+  pub fn seed(mut self, seed: u64) -> S3LAgentBuilder {
+    self.seed = Some(seed);
+    self
+  }
+
+  /// Sets the parameter space, which must have `dims` dimensions. Defaults to `[0, 1]^dims`.
+  pub fn search_space(mut self, space: SearchSpace) -> S3LAgentBuilder {
+    self.space = Some(space);
+    self
+  }
+
+  /// Sets where progress events go. Defaults to [`QuietObserver`].
+  pub fn observer(mut self, observer: Box<dyn Observer + Send>) -> S3LAgentBuilder {
+    self.observer = Some(observer);
+    self
+  }
+
+  /// Adds a constraint known before evaluation. Proposals are re-drawn until they satisfy every one of them.
+  pub fn constraint<C: Constraint + Send + 'static>(mut self, constraint: C) -> S3LAgentBuilder {
+    self.constraints.push(Box::new(constraint));
+    self
+  }
+
+  /// Sets the distance used for avoidance and for moving toward the probabilistic max. Defaults to [`Euclidean`].
+  ///
+  /// A metric with per-dimension settings must have `dims` of them, or [`build`](S3LAgentBuilder::build) panics.
+  pub fn metric<M: Metric + Send + 'static>(mut self, metric: M) -> S3LAgentBuilder {
+    self.metric = Some(Box::new(metric));
+    self
+  }
+
+  /// Saves the agent periodically during learning runs, see [`Checkpoint`].
+  pub fn checkpoint(mut self, checkpoint: Checkpoint) -> S3LAgentBuilder {
+    self.checkpoint = Some(checkpoint);
+    self
+  }
+
+  /// Records what the agent optimizes, so that a saved run can be resumed on it.
+  pub fn objective(mut self, objective: ObjectiveSpec) -> S3LAgentBuilder {
+    self.objective = Some(objective);
+    self
+  }
+
+  /// Sets the size and sampling of the initial design. Defaults to three uniform points.
+  pub fn warm_up(mut self, warm_up: WarmUp) -> S3LAgentBuilder {
+    self.warm_up = warm_up;
+    self
+  }
+
+  pub fn build(self) -> S3LAgent {
+    let rng: StdRng = match self.seed {
+      Some(seed) => StdRng::seed_from_u64(seed),
+      None => StdRng::from_entropy(),
+    };
+    self.build_with_rng(rng)
+  }
+
+  /// Builds the agent around a caller-supplied random number generator, ignoring any seed.
+  pub fn build_with_rng<R: Rng>(self, rng: R) -> S3LAgent<R> {
+    let dims: u32 = self.dims;
+    assert!(dims > 0);
+    let space: SearchSpace = self.space.unwrap_or_else(|| {SearchSpace::unit(dims)});
+    assert_eq!(space.dims(), dims);
+    let direction: Direction = self.direction;
+    let mip: f64 = self.mip;
+    let normalization: Normalization = self.normalization.unwrap_or_else(|| {Normalization::default_for(direction, mip)});
+    assert!(normalization != Normalization::Ratio || self.mip > 0_f64, "ratio normalization needs a positive mip");
+    if let Some(metric) = &self.metric {
+      check_metric_dims(metric.as_ref(), dims);
+    }
+    assert!(self.warm_up.sampling != Sampling::Sobol || dims <= MAX_SOBOL_DIMS, "Sobol sampling supports up to {} dimensions", MAX_SOBOL_DIMS);
+    let mut initial_xi_table: XiTable = XiTable::with_direction(self.direction);
+    if let Some(noise) = self.noise {
+      initial_xi_table.set_kappa(noise.kappa);
+    }
+    if let Some(pareto) = &self.pareto {
+      initial_xi_table.set_pareto_ranking(pareto.ranking == ParetoRanking::ParetoRank);
+    }
+    let initial_upsilon: f64 = 0_f64;
+    S3LAgent {
+      dims: self.dims,
+      mip: self.mip,
+      mip_estimator: self.mip_estimator,
+      normalization,
+      noise: self.noise,
+      pareto: self.pareto,
+      j: self.j,
+      schedule: self.schedule,
+      exponent: self.schedule.initial_exponent(self.j),
+      window_trials: 0,
+      window_successes: 0,
+      refinement: self.refinement,
+      refine_state: RefineState::default(),
+      restart: self.restart,
+      restarts: 0,
+      stagnation: 0,
+      xi_table: initial_xi_table,
+      upsilon: initial_upsilon,
+      rng,
+      space,
+      pending: BTreeMap::new(),
+      next_trial_id: 0,
+      observer: self.observer.unwrap_or_else(|| {Box::new(QuietObserver)}),
+      constraints: self.constraints,
+      metric: self.metric.unwrap_or_else(|| {Box::new(Euclidean)}),
+      checkpoint: self.checkpoint,
+      objective: self.objective,
+      warm_up: self.warm_up,
+      design_drawn: 0,
+      design_start: 0,
+      latin_hypercube: Vec::new(),
+      scratch: Scratch::default(),
+    }
+  }
+}
+
+impl S3LAgent {
+  pub fn builder(dims: u32, mip: f64) -> S3LAgentBuilder {
+    S3LAgentBuilder::new(dims, mip)
+  }
+}
+
+impl<R: Rng> S3LAgent<R> {
+  pub fn dims(&self) -> u32 {
+    self.dims
+  }
+
+  /// The maximum ideal performance, or its current estimate when estimation is enabled.
+  pub fn mip(&self) -> f64 {
+    self.mip
+  }
+
+  pub fn mip_estimator(&self) -> Option<MipEstimator> {
+    self.mip_estimator
+  }
+
+  pub fn direction(&self) -> Direction {
+    self.xi_table.direction()
+  }
+
+  pub fn normalization(&self) -> Normalization {
+    self.normalization
+  }
+
+  /// Scales a performance to `[0, 1]`, where 1 is the ideal performance.
+  pub fn utility(&self, performance: f64) -> f64 {
+    self.normalization.utility(performance, self.mip, self.worst_score().unwrap_or(performance), self.direction())
+  }
+
+  pub(crate) fn best_score(&self) -> Option<f64> {
+    self.xi_table.best().and_then(|x| {self.xi_table.score(x)})
+  }
+
+  pub(crate) fn epoch_best_score(&self) -> Option<f64> {
+    self.xi_table.epoch_best().and_then(|x| {self.xi_table.score(x)})
+  }
+
+  fn worst_score(&self) -> Option<f64> {
+    self.xi_table.epoch_worst().and_then(|x| {self.xi_table.score(x)})
+  }
+
+  pub fn j(&self) -> u32 {
+    self.j
+  }
+
+  /// What the agent optimizes, if it was recorded.
+  pub fn objective(&self) -> Option<&ObjectiveSpec> {
+    self.objective.as_ref()
+  }
+
+  /// Replaces the recorded objective, returning the previous one.
+  pub fn set_objective(&mut self, objective: Option<ObjectiveSpec>) -> Option<ObjectiveSpec> {
+    std::mem::replace(&mut self.objective, objective)
+  }
+
+  /// Every evaluated policy so far.
+  pub fn xi_table(&self) -> &XiTable {
+    &self.xi_table
+  }
+
+  pub fn upsilon(&self) -> f64 {
+    self.upsilon
+  }
+
+  pub fn search_space(&self) -> &SearchSpace {
+    &self.space
+  }
+
+  /// Replaces the observer, returning the previous one.
+  pub fn set_observer(&mut self, observer: Box<dyn Observer + Send>) -> Box<dyn Observer + Send> {
+    std::mem::replace(&mut self.observer, observer)
+  }
+
+  /// Maps a normalized policy to parameter values.
+  pub fn decode(&self, policy: &[f64]) -> Vec<f64> {
+    self.space.decode(policy)
+  }
+
+  /// Records the performance of an evaluated normalized policy. A NaN performance records it as infeasible, as
+  /// [`Objective::try_evaluate`](crate::Objective::try_evaluate) does.
+  pub fn update_xi_table(&mut self, policy: Vec<f64>, performance: f64) -> Done {
+    self.update_xi_table_with_metadata(policy, performance, BTreeMap::new())
+  }
+
+  /// Records the performance of an evaluated normalized policy along with caller-defined metadata.
+  pub fn update_xi_table_with_metadata(&mut self, policy: Vec<f64>, performance: f64, metadata: BTreeMap<String, String>) -> Done {
+    if performance.is_nan() {
+      return self.record_infeasible(policy, metadata);
+    }
+    let previous_best: (Option<f64>, Option<f64>) = (self.best_score(), self.epoch_best_score());
+    self.xi_table.push(policy, performance, metadata);
+    self.report_evaluation(previous_best)
+  }
+
+  /// Reports the entry just recorded, and a new best if it beats the global best in `previous_best`. The epoch's
+  /// best, second in `previous_best`, drives the schedule and restarts.
+  pub(crate) fn report_evaluation(&mut self, previous_best: (Option<f64>, Option<f64>)) -> Done {
+    let direction: Direction = self.xi_table.direction();
+    let new_entry: &XiEntry = self.xi_table.last().unwrap();
+    let new_score: f64 = self.xi_table.score(new_entry).unwrap();
+    let params: Vec<f64> = self.space.decode(&new_entry.policy);
+    self.observer.on_evaluation(new_entry, &params);
+    if previous_best.0.is_none_or(|x| {direction.is_better(new_score, x)}) {
+      self.observer.on_new_best(new_entry, &params);
+    }
+    let improved: bool = previous_best.1.is_none_or(|x| {direction.is_better(new_score, x)});
+    self.advance_schedule(improved);
+    self.track_stagnation(improved)
+  }
+
+  /// Records a normalized policy that violated a constraint. It is avoided like a low-performance minimum.
+  pub fn update_xi_table_infeasible(&mut self, policy: Vec<f64>) -> Done {
+    self.record_infeasible(policy, BTreeMap::new())
+  }
+
+  pub(crate) fn record_infeasible(&mut self, policy: Vec<f64>, metadata: BTreeMap<String, String>) -> Done {
+    let new_entry: &XiEntry = self.xi_table.push_infeasible(policy, metadata);
+    let params: Vec<f64> = self.space.decode(&new_entry.policy);
+    self.observer.on_evaluation(new_entry, &params);
+    self.advance_schedule(false);
+    self.track_stagnation(false)
+  }
+
+  /// Proposes the next normalized policy to evaluate: the next point of the initial design during the warm-up,
+  /// then S3L's own proposal.
+  ///
+  /// Proposals violating a known constraint are re-drawn up to [`MAX_CONSTRAINT_RETRIES`] times; after that the
+  /// last one is returned anyway.
+  pub fn select_policy(&mut self) -> Vec<f64> {
+    let mut policy: Vec<f64> = self.generate_policy();
+    let mut retries: u32 = 0;
+    while retries < MAX_CONSTRAINT_RETRIES && !self.is_feasible(&policy) {
+      policy = self.generate_policy();
+      retries += 1;
+    }
+    self.space.decode_into(&policy, &mut self.scratch.params);
+    self.observer.on_proposal(&self.scratch.params);
+    policy
+  }
+
+  fn generate_policy(&mut self) -> Vec<f64> {
+    self.calculate_upsilon();
+    if self.in_warm_up() {
+      return self.design_policy();
+    }
+    if self.xi_table.epoch_best().is_none() {
+      return self.random_policy();
+    }
+    if let Some(probe) = self.refinement_probe() {
+      return probe;
+    }
+    let mut policy: Vec<f64> = if self.xi_table.epoch().len() > self.warm_up_size() {
+      self.generate_avoidance_point()
+    } else {
+      self.random_policy()
+    };
+    let probabilistic_max: u64 = self.generate_probabilistic_max();
+    let mut scalar: f64 = self.get_vector_delta_scalar();
+    let vector_delta: &mut Vec<f64> = &mut self.scratch.delta;
+    vector_delta.resize(policy.len(), 0_f64);
+    self.metric.delta_into(&policy, &self.xi_table.entries()[probabilistic_max as usize].policy, vector_delta);
+    // While trials are pending the pull is halved until the moved policy keeps clear of them, since every
+    // proposal of a batch is pulled toward the same few entries.
+    let moved: &mut Vec<f64> = &mut self.scratch.moved;
+    loop {
+      moved.clear();
+      moved.extend_from_slice(vector_delta);
+      vector_scalar_multiply_in_place(moved, scalar);
+      sum_vectors_in_place(moved, &policy);
+      self.metric.confine(moved);
+      let metric: &dyn Metric = self.metric.as_ref();
+      if scalar == 0_f64 || self.pending.values().all(|x| {metric.distance(x, moved) >= MIN_PENDING_SEPARATION}) {
+        break;
+      }
+      scalar = if scalar < MIN_PENDING_PULL {0_f64} else {scalar / 2_f64};
+    }
+    policy.copy_from_slice(moved);
+    policy
+  }
+
+  pub(crate) fn random_policy(&mut self) -> Vec<f64> {
+    let mut policy: Vec<f64> = vec![0_f64; self.dims as usize];
+    self.random_policy_into(&mut policy);
+    policy
+  }
+
+  fn random_policy_into(&mut self, policy: &mut [f64]) {
+    for x in policy.iter_mut() {
+      *x = self.rng.gen::<f64>();
+    }
+  }
+
+  fn calculate_upsilon(&mut self) -> Done {
+    if let Some(estimate) = self.mip_estimator.and_then(|x| {x.estimate(&self.xi_table)}) {
+      self.mip = estimate;
+    }
+    let best_score: Option<f64> = self.epoch_best_score();
+    let upsilon_result: f64 = match best_score {
+      Some(xi_table_max_performance) if self.xi_table.epoch().len() > self.warm_up_size() => {
+        let intial_result: f64 = self.utility(xi_table_max_performance);
+        if intial_result >= 0.8 {
+          let base: f64 = 5_f64 * (intial_result - 0.8);
+          0.8 + ((base.powf(self.exponent)) / 5_f64)
+        } else {
+          intial_result
+        }
+      }
+      _ => 0_f64,
+    };
+    if upsilon_result != self.upsilon {
+      self.observer.on_upsilon_change(self.upsilon, upsilon_result);
+    }
+    self.upsilon = upsilon_result;
+    Done
+  }
+
+  /// Draws random points, rejecting ones that lie much closer to a minimum than that minimum lies to a maximum.
+  ///
+  /// Gives up after [`MAX_AVOIDANCE_RETRIES`] rejections and returns the last point drawn.
+  fn generate_avoidance_point(&mut self) -> Vec<f64> {
+    let i_avg_utility: f64 = 3_f64 / 4_f64;
+    let mut initial_vector: Vec<f64> = self.random_policy();
+    let worst: f64 = match self.worst_score() {
+      Some(worst) => worst,
+      None => return initial_vector,
+    };
+    let normalization: Normalization = self.normalization;
+    let mip: f64 = self.mip;
+    let direction: Direction = self.xi_table.direction();
+    for _attempt in 0..MAX_AVOIDANCE_RETRIES {
+      let r: Option<f64> = {
+        let xi_table: &XiTable = &self.xi_table;
+        let metric: &dyn Metric = self.metric.as_ref();
+        let stack: &mut Vec<(usize, f64)> = &mut self.scratch.stack;
+        let score = xi_table.scorer();
+        let epoch_start: u64 = xi_table.epoch_start() as u64;
+        let is_max = |x: &XiEntry| -> bool {
+          score(x).is_some_and(|y| {normalization.utility(y, mip, worst, direction) >= i_avg_utility})
+        };
+        // Infeasible entries and pending trials are avoided like minima, the latter so that concurrent proposals
+        // spread out. Entries from before the last restart are ignored.
+        let closest_pending: Option<(&Vec<f64>, f64)> = self.pending
+        .values()
+        .map(|x| {(x, metric.distance(x, &initial_vector))})
+        .min_by(|a, b| {a.1.partial_cmp(&b.1).unwrap()});
+        // Utility rises with the score, so the epoch's order splits into minima then maxima. A filtered tree walk
+        // wades through the other class, so the sparser class is scanned instead.
+        let order: &[u64] = xi_table.epoch_order();
+        let split: usize = order.partition_point(|x| {!is_max(&xi_table.entries()[*x as usize])});
+        let (mins, maxes): (&[u64], &[u64]) = order.split_at(split);
+        let closest_in = |steps: &mut dyn Iterator<Item = &u64>| -> Option<(&Vec<f64>, f64)> {
+          steps
+          .map(|x| {&xi_table.entries()[*x as usize].policy})
+          .map(|x| {(x, metric.distance(x, &initial_vector))})
+          .min_by(|a, b| {a.1.total_cmp(&b.1)})
+        };
+        let min_count: usize = mins.len() + xi_table.epoch_infeasible().len();
+        let closest_entry: Option<(&Vec<f64>, f64)> = if scan_is_cheaper(min_count, xi_table.len()) {
+          closest_in(&mut mins.iter().chain(xi_table.epoch_infeasible()))
+        } else {
+          xi_table
+          .nearest_with(&initial_vector, metric, |x| {x.step >= epoch_start && !is_max(x)}, stack)
+          .map(|x| {(&x.0.policy, x.1)})
+        };
+        let closest_min: Option<(&Vec<f64>, f64)> = match (closest_entry, closest_pending) {
+          (Some(entry), Some(pending)) => Some(if pending.1 < entry.1 {pending} else {entry}),
+          (entry, pending) => entry.or(pending),
+        };
+        let closest_max: Option<&Vec<f64>> = if scan_is_cheaper(maxes.len(), xi_table.len()) {
+          closest_in(&mut maxes.iter()).map(|x| {x.0})
+        } else {
+          xi_table
+          .nearest_with(&initial_vector, metric, |x| {x.step >= epoch_start && is_max(x)}, stack)
+          .map(|x| {&x.0.policy})
+        };
+        match (closest_min, closest_max) {
+          (Some((closest_min, dmin)), Some(closest_max)) => Some(dmin / metric.distance(closest_min, closest_max)),
+          _ => None,
+        }
+      };
+      let r: f64 = match r {
+        Some(r) => r,
+        None => return initial_vector,
+      };
+      let tryagain: bool = if r < 0.5 {
+        self.rng.gen::<f64>() <= (r * self.upsilon)
+      } else {
+        false
+      };
+      if !tryagain {
+        return initial_vector;
+      }
+      self.random_policy_into(&mut initial_vector);
+    }
+    initial_vector
+  }
+
+  /// The step of the top-ranked entry, or of the runner-up with probability upsilon / 2: where the top of the
+  /// ranking ends up after a pass of random adjacent swaps, since only the first swap can move it.
+  fn generate_probabilistic_max(&mut self) -> u64 {
+    let (first, second): (u64, Option<u64>) = match self.xi_table.top_two() {
+      (Some(first), second) => (first.step, second.map(|x| {x.step})),
+      (None, _) => panic!("generate_probabilistic_max needs a feasible entry"),
+    };
+    match second {
+      Some(second) if self.rng.gen::<f64>() < (self.upsilon / 2_f64) => second,
+      _ => first,
+    }
+  }
+
+  fn get_vector_delta_scalar(&self) -> f64 {
+    (self.upsilon).powf(self.exponent)
+  }
+}
+
+/// Whether the nearest of `count` entries of a table of `len` is found faster by a scan than by a filtered tree walk.
+fn scan_is_cheaper(count: usize, len: usize) -> bool {
+  count * count <= SCAN_FACTOR * len
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::agent::S3LAgent;
+  use crate::learning::learning_S3L;
+  use crate::objective::TwoPeak;
+  use crate::stop::StopCriteria;
+
+  fn policies(seed: u64) -> Vec<Vec<f64>> {
+    let mut agentmodel: S3LAgent = S3LAgent::builder(2, 3.1).seed(seed).build();
+    learning_S3L(&mut agentmodel, &TwoPeak, &StopCriteria::new().max_evaluations(100));
+    agentmodel
+    .xi_table()
+    .iter()
+    .map(|x| {x.policy.clone()})
+    .collect::<Vec<Vec<f64>>>()
+  }
+
+  #[test]
+  fn seeded_agents_replay_bit_for_bit() {
+    let first: Vec<Vec<f64>> = policies(7);
+    assert_eq!(first.len(), 100);
+    assert_eq!(first, policies(7));
+    assert_ne!(first, policies(8));
+  }
+}
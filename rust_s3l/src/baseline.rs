@@ -0,0 +1,412 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::agent::{Done, S3LAgent};
+use crate::performance::Direction;
+
+/// A black-box optimizer driven one evaluation at a time on normalized policies in `[0, 1]^dims`.
+///
+/// Every [`propose`](Optimizer::propose) is followed by exactly one [`observe`](Optimizer::observe) of the same policy,
+/// with `None` for an infeasible one. A NaN performance counts as infeasible too.
+pub trait Optimizer {
+  fn propose(&mut self) -> Vec<f64>;
+
+  fn observe(&mut self, policy: &[f64], performance: Option<f64>) -> Done;
+}
+
+impl<R: Rng> Optimizer for S3LAgent<R> {
+  fn propose(&mut self) -> Vec<f64> {
+    self.select_policy()
+  }
+
+  fn observe(&mut self, policy: &[f64], performance: Option<f64>) -> Done {
+    match performance {
+      Some(performance) => self.update_xi_table(policy.to_vec(), performance),
+      None => self.update_xi_table_infeasible(policy.to_vec()),
+    }
+  }
+}
+
+/// `sign * performance`, so higher is better whatever the direction, with infeasible policies the worst of all.
+fn fitness(direction: Direction, performance: Option<f64>) -> f64 {
+  performance
+  .filter(|x| {!x.is_nan()})
+  .map_or(f64::NEG_INFINITY, |x| {direction.sign() * x})
+}
+
+/// A standard normal draw, by the Box-Muller transform.
+fn standard_normal<R: Rng>(rng: &mut R) -> f64 {
+  let u: f64 = 1_f64 - rng.gen::<f64>();
+  let v: f64 = rng.gen::<f64>();
+  (-2_f64 * u.ln()).sqrt() * (2_f64 * std::f64::consts::PI * v).cos()
+}
+
+fn clamp_unit(policy: &mut [f64]) {
+  for x in policy.iter_mut() {
+    *x = x.clamp(0_f64, 1_f64);
+  }
+}
+
+/// Independent uniform policies.
+#[derive(Clone, Debug)]
+pub struct RandomSearch {
+  dims: u32,
+  rng: StdRng
+}
+
+impl RandomSearch {
+  pub fn new(dims: u32, seed: u64) -> RandomSearch {
+    RandomSearch {
+      dims,
+      rng: StdRng::seed_from_u64(seed),
+    }
+  }
+}
+
+impl Optimizer for RandomSearch {
+  fn propose(&mut self) -> Vec<f64> {
+    (0..self.dims).map(|_x| {self.rng.gen::<f64>()}).collect::<Vec<f64>>()
+  }
+
+  fn observe(&mut self, _policy: &[f64], _performance: Option<f64>) -> Done {
+    Done
+  }
+}
+
+/// The centres of a regular grid with as many points per dimension as `budget` allows, visited in a seeded random
+/// order so a cut-short run still covers the space evenly. Falls back to uniform policies once the grid is exhausted.
+#[derive(Clone, Debug)]
+pub struct GridSearch {
+  dims: u32,
+  per_dim: u64,
+  order: Vec<u64>,
+  next: usize,
+  rng: StdRng
+}
+
+impl GridSearch {
+  pub fn new(dims: u32, budget: usize, seed: u64) -> GridSearch {
+    let mut per_dim: u64 = ((budget as f64).powf(1_f64 / (dims as f64)).floor() as u64).max(1);
+    // The root can land just below an exact power.
+    while (per_dim + 1).checked_pow(dims).is_some_and(|x| {x <= budget as u64}) {
+      per_dim += 1;
+    }
+    let mut rng: StdRng = StdRng::seed_from_u64(seed);
+    let mut order: Vec<u64> = (0..per_dim.pow(dims)).collect::<Vec<u64>>();
+    order.shuffle(&mut rng);
+    GridSearch {
+      dims,
+      per_dim,
+      order,
+      next: 0,
+      rng,
+    }
+  }
+
+  pub fn points_per_dim(&self) -> u64 {
+    self.per_dim
+  }
+}
+
+impl Optimizer for GridSearch {
+  fn propose(&mut self) -> Vec<f64> {
+    let mut index: u64 = match self.order.get(self.next) {
+      Some(index) => *index,
+      None => return (0..self.dims).map(|_x| {self.rng.gen::<f64>()}).collect::<Vec<f64>>(),
+    };
+    self.next += 1;
+    let mut policy: Vec<f64> = Vec::with_capacity(self.dims as usize);
+    for _dim in 0..self.dims {
+      policy.push(((index % self.per_dim) as f64 + 0.5) / (self.per_dim as f64));
+      index /= self.per_dim;
+    }
+    policy
+  }
+
+  fn observe(&mut self, _policy: &[f64], _performance: Option<f64>) -> Done {
+    Done
+  }
+}
+
+/// Stochastic hill climbing: a Gaussian step of fixed size from the incumbent, kept when it is no worse.
+#[derive(Clone, Debug)]
+pub struct HillClimbing {
+  dims: u32,
+  direction: Direction,
+  step: f64,
+  incumbent: Option<(Vec<f64>, f64)>,
+  rng: StdRng
+}
+
+impl HillClimbing {
+  /// Steps with a standard deviation of 0.1 of the normalized range.
+  pub fn new(dims: u32, direction: Direction, seed: u64) -> HillClimbing {
+    HillClimbing {
+      dims,
+      direction,
+      step: 0.1,
+      incumbent: None,
+      rng: StdRng::seed_from_u64(seed),
+    }
+  }
+
+  pub fn step(mut self, step: f64) -> HillClimbing {
+    self.step = step;
+    self
+  }
+}
+
+impl Optimizer for HillClimbing {
+  fn propose(&mut self) -> Vec<f64> {
+    let mut policy: Vec<f64> = match &self.incumbent {
+      Some((incumbent, _fitness)) => incumbent.clone(),
+      None => return (0..self.dims).map(|_x| {self.rng.gen::<f64>()}).collect::<Vec<f64>>(),
+    };
+    for x in policy.iter_mut() {
+      *x += self.step * standard_normal(&mut self.rng);
+    }
+    clamp_unit(&mut policy);
+    policy
+  }
+
+  fn observe(&mut self, policy: &[f64], performance: Option<f64>) -> Done {
+    let fitness: f64 = fitness(self.direction, performance);
+    if self.incumbent.as_ref().is_none_or(|x| {fitness >= x.1}) {
+      self.incumbent = Some((policy.to_vec(), fitness));
+    }
+    Done
+  }
+}
+
+#[derive(Clone, Debug)]
+enum NelderMeadPhase {
+  /// Evaluating the vertices of a new simplex.
+  Initial,
+  Reflect,
+  Expand { reflected: (Vec<f64>, f64) },
+  Contract { reflected: (Vec<f64>, f64) },
+  /// Re-evaluating the shrunk vertices, best excluded.
+  Shrink { next: usize }
+}
+
+/// The Nelder-Mead simplex method, clamped to the unit cube and restarted from a random point once the simplex
+/// collapses.
+#[derive(Clone, Debug)]
+pub struct NelderMead {
+  dims: u32,
+  direction: Direction,
+  /// Vertices and their fitness, best first once the simplex is complete.
+  simplex: Vec<(Vec<f64>, f64)>,
+  start: Vec<f64>,
+  phase: NelderMeadPhase,
+  rng: StdRng
+}
+
+/// Side of the initial simplex in the normalized range.
+const NELDER_MEAD_INITIAL_SIZE: f64 = 0.1;
+
+/// Simplex diameter under which the search restarts.
+const NELDER_MEAD_MIN_SIZE: f64 = 1e-9;
+
+impl NelderMead {
+  pub fn new(dims: u32, direction: Direction, seed: u64) -> NelderMead {
+    let mut rng: StdRng = StdRng::seed_from_u64(seed);
+    let start: Vec<f64> = (0..dims).map(|_x| {rng.gen::<f64>()}).collect::<Vec<f64>>();
+    NelderMead {
+      dims,
+      direction,
+      simplex: Vec::new(),
+      start,
+      phase: NelderMeadPhase::Initial,
+      rng,
+    }
+  }
+
+  /// The centroid of every vertex but the worst.
+  fn centroid(&self) -> Vec<f64> {
+    let n: usize = self.simplex.len() - 1;
+    let mut centroid: Vec<f64> = vec![0_f64; self.dims as usize];
+    for (vertex, _fitness) in self.simplex.iter().take(n) {
+      for (c, x) in centroid.iter_mut().zip(vertex.iter()) {
+        *c += x / (n as f64);
+      }
+    }
+    centroid
+  }
+
+  /// `centroid + coefficient * (point - centroid)`, clamped.
+  fn along(centroid: &[f64], point: &[f64], coefficient: f64) -> Vec<f64> {
+    let mut policy: Vec<f64> = centroid
+    .iter()
+    .zip(point.iter())
+    .map(|(c, x)| {c + coefficient * (x - c)})
+    .collect::<Vec<f64>>();
+    clamp_unit(&mut policy);
+    policy
+  }
+
+  fn worst(&self) -> &(Vec<f64>, f64) {
+    self.simplex.last().unwrap()
+  }
+
+  fn replace_worst(&mut self, vertex: (Vec<f64>, f64)) -> Done {
+    *self.simplex.last_mut().unwrap() = vertex;
+    self.next_iteration()
+  }
+
+  /// Sorts the simplex and reflects again, or restarts when it has collapsed.
+  fn next_iteration(&mut self) -> Done {
+    self.simplex.sort_by(|a, b| {b.1.partial_cmp(&a.1).unwrap()});
+    let best: &[f64] = &self.simplex[0].0;
+    let diameter: f64 = self.simplex
+    .iter()
+    .map(|(x, _fitness)| {x.iter().zip(best.iter()).fold(0_f64, |a, y| {a.max((y.0 - y.1).abs())})})
+    .fold(0_f64, f64::max);
+    if diameter < NELDER_MEAD_MIN_SIZE {
+      self.start = (0..self.dims).map(|_x| {self.rng.gen::<f64>()}).collect::<Vec<f64>>();
+      self.simplex.clear();
+      self.phase = NelderMeadPhase::Initial;
+    } else {
+      self.phase = NelderMeadPhase::Reflect;
+    }
+    Done
+  }
+}
+
+impl Optimizer for NelderMead {
+  fn propose(&mut self) -> Vec<f64> {
+    match &self.phase {
+      NelderMeadPhase::Initial => {
+        let mut vertex: Vec<f64> = self.start.clone();
+        if let Some(axis) = self.simplex.len().checked_sub(1) {
+          vertex[axis] += if vertex[axis] + NELDER_MEAD_INITIAL_SIZE <= 1_f64 {
+            NELDER_MEAD_INITIAL_SIZE
+          } else {
+            -NELDER_MEAD_INITIAL_SIZE
+          };
+        }
+        vertex
+      }
+      NelderMeadPhase::Reflect => NelderMead::along(&self.centroid(), &self.worst().0, -1_f64),
+      NelderMeadPhase::Expand { reflected } => NelderMead::along(&self.centroid(), &reflected.0, 2_f64),
+      NelderMeadPhase::Contract { reflected } => {
+        if reflected.1 > self.worst().1 {
+          NelderMead::along(&self.centroid(), &reflected.0, 0.5)
+        } else {
+          NelderMead::along(&self.centroid(), &self.worst().0, 0.5)
+        }
+      }
+      NelderMeadPhase::Shrink { next } => self.simplex[*next].0.clone(),
+    }
+  }
+
+  fn observe(&mut self, policy: &[f64], performance: Option<f64>) -> Done {
+    let vertex: (Vec<f64>, f64) = (policy.to_vec(), fitness(self.direction, performance));
+    let n: usize = self.dims as usize;
+    match self.phase.clone() {
+      NelderMeadPhase::Initial => {
+        self.simplex.push(vertex);
+        if self.simplex.len() > n {
+          self.next_iteration();
+        }
+        Done
+      }
+      NelderMeadPhase::Reflect => {
+        if vertex.1 > self.simplex[0].1 {
+          self.phase = NelderMeadPhase::Expand { reflected: vertex };
+          Done
+        } else if vertex.1 > self.simplex[n - 1].1 {
+          self.replace_worst(vertex)
+        } else {
+          self.phase = NelderMeadPhase::Contract { reflected: vertex };
+          Done
+        }
+      }
+      NelderMeadPhase::Expand { reflected } => {
+        if vertex.1 > reflected.1 {self.replace_worst(vertex)} else {self.replace_worst(reflected)}
+      }
+      NelderMeadPhase::Contract { reflected } => {
+        if vertex.1 > reflected.1.max(self.worst().1) {
+          return self.replace_worst(vertex);
+        }
+        let best: Vec<f64> = self.simplex[0].0.clone();
+        for (x, _fitness) in self.simplex.iter_mut().skip(1) {
+          *x = NelderMead::along(&best, x, 0.5);
+        }
+        self.phase = NelderMeadPhase::Shrink { next: 1 };
+        Done
+      }
+      NelderMeadPhase::Shrink { next } => {
+        self.simplex[next] = vertex;
+        if next < n {
+          self.phase = NelderMeadPhase::Shrink { next: next + 1 };
+          Done
+        } else {
+          self.next_iteration()
+        }
+      }
+    }
+  }
+}
+
+/// A (μ/μ, λ) evolution strategy with self-adaptive step sizes: each offspring mutates the parents' mean with its own
+/// log-normally perturbed step, and the μ best offspring give the next mean and step.
+#[derive(Clone, Debug)]
+pub struct EvolutionStrategy {
+  direction: Direction,
+  mean: Vec<f64>,
+  step: f64,
+  mu: usize,
+  lambda: usize,
+  /// Offspring of the current generation with their step and fitness.
+  offspring: Vec<(Vec<f64>, f64, f64)>,
+  proposed_step: f64,
+  rng: StdRng
+}
+
+impl EvolutionStrategy {
+  /// λ = 4 + ⌊3 ln dims⌋ offspring and μ = λ / 2 parents, starting at a random mean with a step of 0.3.
+  pub fn new(dims: u32, direction: Direction, seed: u64) -> EvolutionStrategy {
+    let mut rng: StdRng = StdRng::seed_from_u64(seed);
+    let lambda: usize = 4 + (3_f64 * (dims as f64).ln()).floor() as usize;
+    EvolutionStrategy {
+      direction,
+      mean: (0..dims).map(|_x| {rng.gen::<f64>()}).collect::<Vec<f64>>(),
+      step: 0.3,
+      mu: lambda / 2,
+      lambda,
+      offspring: Vec::new(),
+      proposed_step: 0.3,
+      rng,
+    }
+  }
+}
+
+impl Optimizer for EvolutionStrategy {
+  fn propose(&mut self) -> Vec<f64> {
+    let tau: f64 = 1_f64 / (2_f64 * self.mean.len() as f64).sqrt();
+    self.proposed_step = self.step * (tau * standard_normal(&mut self.rng)).exp();
+    let mut policy: Vec<f64> = self.mean.clone();
+    for x in policy.iter_mut() {
+      *x += self.proposed_step * standard_normal(&mut self.rng);
+    }
+    clamp_unit(&mut policy);
+    policy
+  }
+
+  fn observe(&mut self, policy: &[f64], performance: Option<f64>) -> Done {
+    self.offspring.push((policy.to_vec(), self.proposed_step, fitness(self.direction, performance)));
+    if self.offspring.len() < self.lambda {
+      return Done;
+    }
+    self.offspring.sort_by(|a, b| {b.2.partial_cmp(&a.2).unwrap()});
+    let mu: f64 = self.mu as f64;
+    for (i, m) in self.mean.iter_mut().enumerate() {
+      *m = self.offspring.iter().take(self.mu).map(|x| {x.0[i]}).sum::<f64>() / mu;
+    }
+    self.step = (self.offspring.iter().take(self.mu).map(|x| {x.1.ln()}).sum::<f64>() / mu).exp();
+    self.offspring.clear();
+    Done
+  }
+}
@@ -0,0 +1,69 @@
+use rand::Rng;
+use rayon::prelude::*;
+
+use crate::agent::S3LAgent;
+use crate::objective::Objective;
+use crate::trial::Trial;
+use crate::xi_table::XiEntry;
+
+impl<R: Rng> S3LAgent<R> {
+  /// Asks for `n` trials at once; each proposal avoids the ones still pending before it, and is only pulled toward
+  /// the probabilistic max as far as it stays [`MIN_PENDING_SEPARATION`](crate::MIN_PENDING_SEPARATION) from them.
+  pub fn select_batch(&mut self, n: usize) -> Vec<Trial> {
+    (0..n)
+    .map(|_x| {self.ask()})
+    .collect::<Vec<Trial>>()
+  }
+}
+
+/// Selects `n` policies, evaluates them in parallel and records every result.
+pub fn batch_learn_step<R, O>(agentmodel: &mut S3LAgent<R>, objective: &O, n: usize) -> Vec<XiEntry>
+where
+  R: Rng,
+  O: Objective + Sync + ?Sized,
+{
+  let trials: Vec<Trial> = agentmodel.select_batch(n);
+  let feasible: Vec<bool> = trials
+  .iter()
+  .map(|x| {agentmodel.is_feasible(&x.policy)})
+  .collect::<Vec<bool>>();
+  let performances: Vec<Option<f64>> = trials
+  .par_iter()
+  .zip(feasible.par_iter())
+  .map(|(x, feasible)| {if *feasible {objective.try_evaluate(&x.params)} else {None}})
+  .collect::<Vec<Option<f64>>>();
+  let mut new_entries: Vec<XiEntry> = Vec::new();
+  for (trial, performance) in trials.into_iter().zip(performances) {
+    match performance {
+      Some(performance) => agentmodel.tell(trial.id, performance),
+      None => agentmodel.tell_infeasible(trial.id),
+    };
+    new_entries.push(agentmodel.xi_table().last().unwrap().clone());
+  }
+  new_entries
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::agent::{S3LAgent, MIN_PENDING_SEPARATION};
+  use crate::learning::learning_S3L;
+  use crate::objective::TwoPeak;
+  use crate::stop::StopCriteria;
+  use crate::trial::Trial;
+  use crate::vector::get_l2_n_dist;
+
+  #[test]
+  fn batch_spreads_out_once_exploiting() {
+    for seed in 0..4 {
+      let mut agentmodel: S3LAgent = S3LAgent::builder(2, 3.1).seed(seed).build();
+      learning_S3L(&mut agentmodel, &TwoPeak, &StopCriteria::new().max_evaluations(150));
+      let batch: Vec<Trial> = agentmodel.select_batch(8);
+      for (i, trial) in batch.iter().enumerate() {
+        for earlier in batch[..i].iter() {
+          let distance: f64 = get_l2_n_dist(&trial.policy, &earlier.policy);
+          assert!(distance >= MIN_PENDING_SEPARATION, "seed {}: trials {} and {} within {}", seed, earlier.id, trial.id, distance);
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,455 @@
+use std::f64::consts::{E, PI};
+
+use crate::agent::{S3LAgent, S3LAgentBuilder};
+use crate::objective::{Objective, ObjectiveSpec, TwoPeak};
+use crate::performance::{Direction, Normalization};
+use crate::space::SearchSpace;
+
+/// A test function with a known optimum, evaluated on parameter values in its standard domain.
+pub trait Benchmark: Objective {
+  fn name(&self) -> &'static str;
+
+  fn dims(&self) -> u32;
+
+  /// The standard domain.
+  fn search_space(&self) -> SearchSpace;
+
+  /// The optimal performance.
+  fn optimum(&self) -> f64;
+
+  /// Every parameter vector reaching the optimum.
+  fn optimizers(&self) -> Vec<Vec<f64>>;
+
+  /// Benchmarks are minimized unless they say otherwise.
+  fn direction(&self) -> Direction {
+    Direction::Minimize
+  }
+
+  /// [`Normalization::MinMax`], since most optima are 0 or negative.
+  fn normalization(&self) -> Normalization {
+    Normalization::MinMax
+  }
+}
+
+impl S3LAgent {
+  /// A builder already set to the benchmark's dimensions, domain, direction and optimum as mip, recording the
+  /// benchmark as its objective.
+  pub fn benchmark_builder<B: Benchmark + ?Sized>(benchmark: &B) -> S3LAgentBuilder {
+    S3LAgentBuilder::new(benchmark.dims(), benchmark.optimum())
+    .objective(ObjectiveSpec::Benchmark(benchmark.name().to_string()))
+    .direction(benchmark.direction())
+    .normalization(benchmark.normalization())
+    .search_space(benchmark.search_space())
+  }
+}
+
+/// Every benchmark defined for `dims` dimensions: the scalable ones, plus Branin at 2 and Hartmann at 3 and 6.
+pub fn benchmark_suite(dims: u32) -> Vec<Box<dyn Benchmark + Send + Sync>> {
+  let mut suite: Vec<Box<dyn Benchmark + Send + Sync>> = vec![
+    Box::new(Sphere { dims }),
+    Box::new(Rastrigin { dims }),
+    Box::new(Ackley { dims }),
+    Box::new(Griewank { dims }),
+    Box::new(Schwefel { dims }),
+  ];
+  if dims >= 2 {
+    suite.push(Box::new(Rosenbrock { dims }));
+  }
+  match dims {
+    2 => suite.push(Box::new(Branin)),
+    3 => suite.push(Box::new(Hartmann::Three)),
+    6 => suite.push(Box::new(Hartmann::Six)),
+    _ => {}
+  }
+  suite
+}
+
+fn cube(dims: u32, low: f64, high: f64) -> SearchSpace {
+  (0..dims).fold(SearchSpace::new(), |a, _x| {a.continuous(low, high)})
+}
+
+/// `Σ x²` on `[-5.12, 5.12]^d`, minimal at the origin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sphere {
+  pub dims: u32
+}
+
+impl Objective for Sphere {
+  fn evaluate(&self, policy: &[f64]) -> f64 {
+    policy.iter().map(|x| {x.powi(2)}).sum::<f64>()
+  }
+}
+
+impl Benchmark for Sphere {
+  fn name(&self) -> &'static str {
+    "sphere"
+  }
+
+  fn dims(&self) -> u32 {
+    self.dims
+  }
+
+  fn search_space(&self) -> SearchSpace {
+    cube(self.dims, -5.12, 5.12)
+  }
+
+  fn optimum(&self) -> f64 {
+    0_f64
+  }
+
+  fn optimizers(&self) -> Vec<Vec<f64>> {
+    vec![vec![0_f64; self.dims as usize]]
+  }
+}
+
+/// A sphere with a cosine ripple on `[-5.12, 5.12]^d`: regularly spaced local minima around the origin.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rastrigin {
+  pub dims: u32
+}
+
+impl Objective for Rastrigin {
+  fn evaluate(&self, policy: &[f64]) -> f64 {
+    10_f64 * (policy.len() as f64) + policy
+    .iter()
+    .map(|x| {x.powi(2) - 10_f64 * (2_f64 * PI * x).cos()})
+    .sum::<f64>()
+  }
+}
+
+impl Benchmark for Rastrigin {
+  fn name(&self) -> &'static str {
+    "rastrigin"
+  }
+
+  fn dims(&self) -> u32 {
+    self.dims
+  }
+
+  fn search_space(&self) -> SearchSpace {
+    cube(self.dims, -5.12, 5.12)
+  }
+
+  fn optimum(&self) -> f64 {
+    0_f64
+  }
+
+  fn optimizers(&self) -> Vec<Vec<f64>> {
+    vec![vec![0_f64; self.dims as usize]]
+  }
+}
+
+/// The banana-shaped valley on `[-5, 10]^d`, minimal at all ones. Needs at least 2 dimensions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rosenbrock {
+  pub dims: u32
+}
+
+impl Objective for Rosenbrock {
+  fn evaluate(&self, policy: &[f64]) -> f64 {
+    policy
+    .windows(2)
+    .map(|x| {100_f64 * (x[1] - x[0].powi(2)).powi(2) + (x[0] - 1_f64).powi(2)})
+    .sum::<f64>()
+  }
+}
+
+impl Benchmark for Rosenbrock {
+  fn name(&self) -> &'static str {
+    "rosenbrock"
+  }
+
+  fn dims(&self) -> u32 {
+    self.dims
+  }
+
+  fn search_space(&self) -> SearchSpace {
+    cube(self.dims, -5_f64, 10_f64)
+  }
+
+  fn optimum(&self) -> f64 {
+    0_f64
+  }
+
+  fn optimizers(&self) -> Vec<Vec<f64>> {
+    vec![vec![1_f64; self.dims as usize]]
+  }
+}
+
+/// A nearly flat outer region with a deep hole at the origin, on `[-32.768, 32.768]^d`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ackley {
+  pub dims: u32
+}
+
+impl Objective for Ackley {
+  fn evaluate(&self, policy: &[f64]) -> f64 {
+    let n: f64 = policy.len() as f64;
+    let square_mean: f64 = policy.iter().map(|x| {x.powi(2)}).sum::<f64>() / n;
+    let cosine_mean: f64 = policy.iter().map(|x| {(2_f64 * PI * x).cos()}).sum::<f64>() / n;
+    -20_f64 * (-0.2 * square_mean.sqrt()).exp() - cosine_mean.exp() + 20_f64 + E
+  }
+}
+
+impl Benchmark for Ackley {
+  fn name(&self) -> &'static str {
+    "ackley"
+  }
+
+  fn dims(&self) -> u32 {
+    self.dims
+  }
+
+  fn search_space(&self) -> SearchSpace {
+    cube(self.dims, -32.768, 32.768)
+  }
+
+  fn optimum(&self) -> f64 {
+    0_f64
+  }
+
+  fn optimizers(&self) -> Vec<Vec<f64>> {
+    vec![vec![0_f64; self.dims as usize]]
+  }
+}
+
+/// A wide bowl with many shallow local minima, on `[-600, 600]^d`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Griewank {
+  pub dims: u32
+}
+
+impl Objective for Griewank {
+  fn evaluate(&self, policy: &[f64]) -> f64 {
+    let sum: f64 = policy.iter().map(|x| {x.powi(2) / 4000_f64}).sum::<f64>();
+    let product: f64 = policy
+    .iter()
+    .enumerate()
+    .map(|(i, x)| {(x / ((i + 1) as f64).sqrt()).cos()})
+    .product::<f64>();
+    sum - product + 1_f64
+  }
+}
+
+impl Benchmark for Griewank {
+  fn name(&self) -> &'static str {
+    "griewank"
+  }
+
+  fn dims(&self) -> u32 {
+    self.dims
+  }
+
+  fn search_space(&self) -> SearchSpace {
+    cube(self.dims, -600_f64, 600_f64)
+  }
+
+  fn optimum(&self) -> f64 {
+    0_f64
+  }
+
+  fn optimizers(&self) -> Vec<Vec<f64>> {
+    vec![vec![0_f64; self.dims as usize]]
+  }
+}
+
+/// A deceptive landscape on `[-500, 500]^d` whose best local minima are far apart, the global one near the corner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Schwefel {
+  pub dims: u32
+}
+
+impl Objective for Schwefel {
+  fn evaluate(&self, policy: &[f64]) -> f64 {
+    418.9829 * (policy.len() as f64) - policy
+    .iter()
+    .map(|x| {x * x.abs().sqrt().sin()})
+    .sum::<f64>()
+  }
+}
+
+impl Benchmark for Schwefel {
+  fn name(&self) -> &'static str {
+    "schwefel"
+  }
+
+  fn dims(&self) -> u32 {
+    self.dims
+  }
+
+  fn search_space(&self) -> SearchSpace {
+    cube(self.dims, -500_f64, 500_f64)
+  }
+
+  /// 0 up to the rounding of the published constant.
+  fn optimum(&self) -> f64 {
+    0_f64
+  }
+
+  fn optimizers(&self) -> Vec<Vec<f64>> {
+    vec![vec![420.9687; self.dims as usize]]
+  }
+}
+
+/// The 2-D Branin-Hoo function on `[-5, 10] x [0, 15]`, with three global minima.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Branin;
+
+impl Objective for Branin {
+  fn evaluate(&self, policy: &[f64]) -> f64 {
+    let b: f64 = 5.1 / (4_f64 * PI.powi(2));
+    let c: f64 = 5_f64 / PI;
+    let t: f64 = 1_f64 / (8_f64 * PI);
+    (policy[1] - b * policy[0].powi(2) + c * policy[0] - 6_f64).powi(2) + 10_f64 * (1_f64 - t) * policy[0].cos() + 10_f64
+  }
+}
+
+impl Benchmark for Branin {
+  fn name(&self) -> &'static str {
+    "branin"
+  }
+
+  fn dims(&self) -> u32 {
+    2
+  }
+
+  fn search_space(&self) -> SearchSpace {
+    SearchSpace::new()
+    .continuous(-5_f64, 10_f64)
+    .continuous(0_f64, 15_f64)
+  }
+
+  fn optimum(&self) -> f64 {
+    0.397887
+  }
+
+  fn optimizers(&self) -> Vec<Vec<f64>> {
+    vec![vec![-PI, 12.275], vec![PI, 2.275], vec![9.42478, 2.475]]
+  }
+}
+
+/// The Hartmann functions on the unit cube: four Gaussian-like wells in 3 or 6 dimensions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Hartmann {
+  Three,
+  Six
+}
+
+const HARTMANN_ALPHA: [f64; 4] = [1.0, 1.2, 3.0, 3.2];
+
+const HARTMANN3_A: [[f64; 3]; 4] = [[3.0, 10.0, 30.0], [0.1, 10.0, 35.0], [3.0, 10.0, 30.0], [0.1, 10.0, 35.0]];
+
+const HARTMANN3_P: [[f64; 3]; 4] = [
+  [0.3689, 0.1170, 0.2673],
+  [0.4699, 0.4387, 0.7470],
+  [0.1091, 0.8732, 0.5547],
+  [0.0381, 0.5743, 0.8828]
+];
+
+const HARTMANN6_A: [[f64; 6]; 4] = [
+  [10.0, 3.0, 17.0, 3.5, 1.7, 8.0],
+  [0.05, 10.0, 17.0, 0.1, 8.0, 14.0],
+  [3.0, 3.5, 1.7, 10.0, 17.0, 8.0],
+  [17.0, 8.0, 0.05, 10.0, 0.1, 14.0]
+];
+
+const HARTMANN6_P: [[f64; 6]; 4] = [
+  [0.1312, 0.1696, 0.5569, 0.0124, 0.8283, 0.5886],
+  [0.2329, 0.4135, 0.8307, 0.3736, 0.1004, 0.9991],
+  [0.2348, 0.1451, 0.3522, 0.2883, 0.3047, 0.6650],
+  [0.4047, 0.8828, 0.8732, 0.5743, 0.1091, 0.0381]
+];
+
+fn hartmann(policy: &[f64], a: &[&[f64]], p: &[&[f64]]) -> f64 {
+  -(0..4)
+  .map(|i| {
+    let exponent: f64 = policy
+    .iter()
+    .enumerate()
+    .map(|(j, x)| {a[i][j] * (x - p[i][j]).powi(2)})
+    .sum::<f64>();
+    HARTMANN_ALPHA[i] * (-exponent).exp()
+  })
+  .sum::<f64>()
+}
+
+impl Objective for Hartmann {
+  fn evaluate(&self, policy: &[f64]) -> f64 {
+    match self {
+      Hartmann::Three => hartmann(
+        policy,
+        &HARTMANN3_A.iter().map(|x| {&x[..]}).collect::<Vec<&[f64]>>(),
+        &HARTMANN3_P.iter().map(|x| {&x[..]}).collect::<Vec<&[f64]>>(),
+      ),
+      Hartmann::Six => hartmann(
+        policy,
+        &HARTMANN6_A.iter().map(|x| {&x[..]}).collect::<Vec<&[f64]>>(),
+        &HARTMANN6_P.iter().map(|x| {&x[..]}).collect::<Vec<&[f64]>>(),
+      ),
+    }
+  }
+}
+
+impl Benchmark for Hartmann {
+  fn name(&self) -> &'static str {
+    match self {
+      Hartmann::Three => "hartmann3",
+      Hartmann::Six => "hartmann6",
+    }
+  }
+
+  fn dims(&self) -> u32 {
+    match self {
+      Hartmann::Three => 3,
+      Hartmann::Six => 6,
+    }
+  }
+
+  fn search_space(&self) -> SearchSpace {
+    SearchSpace::unit(self.dims())
+  }
+
+  fn optimum(&self) -> f64 {
+    match self {
+      Hartmann::Three => -3.86278,
+      Hartmann::Six => -3.32237,
+    }
+  }
+
+  fn optimizers(&self) -> Vec<Vec<f64>> {
+    match self {
+      Hartmann::Three => vec![vec![0.114614, 0.555649, 0.852547]],
+      Hartmann::Six => vec![vec![0.20169, 0.150011, 0.476874, 0.275332, 0.311652, 0.6573]],
+    }
+  }
+}
+
+impl Benchmark for TwoPeak {
+  fn name(&self) -> &'static str {
+    "two-peak"
+  }
+
+  fn dims(&self) -> u32 {
+    2
+  }
+
+  fn search_space(&self) -> SearchSpace {
+    SearchSpace::unit(2)
+  }
+
+  fn optimum(&self) -> f64 {
+    3_f64
+  }
+
+  fn optimizers(&self) -> Vec<Vec<f64>> {
+    vec![vec![0.7, 0.7]]
+  }
+
+  fn direction(&self) -> Direction {
+    Direction::Maximize
+  }
+
+  fn normalization(&self) -> Normalization {
+    Normalization::Ratio
+  }
+}
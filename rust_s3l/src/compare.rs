@@ -0,0 +1,220 @@
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::S3LAgent;
+use crate::baseline::{EvolutionStrategy, GridSearch, HillClimbing, NelderMead, Optimizer, RandomSearch};
+use crate::benchmark::Benchmark;
+use crate::performance::Direction;
+use crate::space::SearchSpace;
+
+type Build = dyn Fn(&dyn Benchmark, usize, u64) -> Box<dyn Optimizer + Send> + Send + Sync;
+
+/// A named way to build an optimizer for a benchmark, an evaluation budget and a seed.
+pub struct Contender {
+  pub name: String,
+  build: Box<Build>
+}
+
+impl Contender {
+  pub fn new<S, F>(name: S, build: F) -> Contender
+  where
+    S: Into<String>,
+    F: Fn(&dyn Benchmark, usize, u64) -> Box<dyn Optimizer + Send> + Send + Sync + 'static,
+  {
+    Contender {
+      name: name.into(),
+      build: Box::new(build),
+    }
+  }
+
+  /// S3L with default settings and the benchmark's optimum as mip.
+  pub fn s3l() -> Contender {
+    Contender::new("s3l", |benchmark, _budget, seed| {
+      Box::new(S3LAgent::benchmark_builder(benchmark).seed(seed).build()) as Box<dyn Optimizer + Send>
+    })
+  }
+
+  pub fn random_search() -> Contender {
+    Contender::new("random", |benchmark, _budget, seed| {
+      Box::new(RandomSearch::new(benchmark.dims(), seed)) as Box<dyn Optimizer + Send>
+    })
+  }
+
+  pub fn grid_search() -> Contender {
+    Contender::new("grid", |benchmark, budget, seed| {
+      Box::new(GridSearch::new(benchmark.dims(), budget, seed)) as Box<dyn Optimizer + Send>
+    })
+  }
+
+  pub fn hill_climbing() -> Contender {
+    Contender::new("hill-climbing", |benchmark, _budget, seed| {
+      Box::new(HillClimbing::new(benchmark.dims(), benchmark.direction(), seed)) as Box<dyn Optimizer + Send>
+    })
+  }
+
+  pub fn nelder_mead() -> Contender {
+    Contender::new("nelder-mead", |benchmark, _budget, seed| {
+      Box::new(NelderMead::new(benchmark.dims(), benchmark.direction(), seed)) as Box<dyn Optimizer + Send>
+    })
+  }
+
+  pub fn evolution_strategy() -> Contender {
+    Contender::new("evolution-strategy", |benchmark, _budget, seed| {
+      Box::new(EvolutionStrategy::new(benchmark.dims(), benchmark.direction(), seed)) as Box<dyn Optimizer + Send>
+    })
+  }
+
+  /// S3L followed by every built-in baseline.
+  pub fn all() -> Vec<Contender> {
+    vec![
+      Contender::s3l(),
+      Contender::random_search(),
+      Contender::grid_search(),
+      Contender::hill_climbing(),
+      Contender::nelder_mead(),
+      Contender::evolution_strategy(),
+    ]
+  }
+}
+
+/// How many runs [`compare`] makes and what it reports.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompareSettings {
+  /// Evaluations per run.
+  pub budget: usize,
+  /// Runs per contender, seeded `0..seeds`.
+  pub seeds: u64,
+  /// Quantiles of the best-so-far curves, in `[0, 1]`.
+  pub quantiles: Vec<f64>,
+  /// Performance counting as success. Defaults to within `1e-2` of the benchmark's optimum.
+  pub target: Option<f64>
+}
+
+impl Default for CompareSettings {
+  fn default() -> CompareSettings {
+    CompareSettings {
+      budget: 100,
+      seeds: 20,
+      quantiles: vec![0.25, 0.5, 0.75],
+      target: None,
+    }
+  }
+}
+
+/// One contender's results over every seed of a [`compare`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ContenderReport {
+  pub name: String,
+  /// The mean over seeds of the best performance after each evaluation.
+  pub mean: Vec<f64>,
+  /// For each of the settings' quantiles, that quantile over seeds of the best performance after each evaluation.
+  pub quantiles: Vec<(f64, Vec<f64>)>,
+  /// Per seed, the evaluations it took to reach the target, or `None` if it never did.
+  pub evaluations_to_target: Vec<Option<usize>>
+}
+
+impl ContenderReport {
+  /// The fraction of seeds that reached the target.
+  pub fn success_rate(&self) -> f64 {
+    let successes: usize = self.evaluations_to_target.iter().filter(|x| {x.is_some()}).count();
+    successes as f64 / self.evaluations_to_target.len() as f64
+  }
+
+  /// The mean evaluations to target over the seeds that reached it.
+  pub fn mean_evaluations_to_target(&self) -> Option<f64> {
+    let hits: Vec<f64> = self.evaluations_to_target
+    .iter()
+    .flatten()
+    .map(|x| {*x as f64})
+    .collect::<Vec<f64>>();
+    if hits.is_empty() {None} else {Some(hits.iter().sum::<f64>() / hits.len() as f64)}
+  }
+}
+
+/// The outcome of [`compare`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Comparison {
+  pub benchmark: String,
+  pub optimum: f64,
+  pub target: f64,
+  pub direction: Direction,
+  pub contenders: Vec<ContenderReport>
+}
+
+/// The best-so-far curve of one run and the evaluations it took to reach `target`.
+fn run_once(benchmark: &dyn Benchmark, optimizer: &mut dyn Optimizer, budget: usize, target: f64) -> (Vec<f64>, Option<usize>) {
+  let space: SearchSpace = benchmark.search_space();
+  let direction: Direction = benchmark.direction();
+  let mut best: f64 = -direction.sign() * f64::INFINITY;
+  let mut curve: Vec<f64> = Vec::with_capacity(budget);
+  let mut hit: Option<usize> = None;
+  for evaluation in 1..=budget {
+    let policy: Vec<f64> = optimizer.propose();
+    let performance: Option<f64> = benchmark.try_evaluate(&space.decode(&policy));
+    optimizer.observe(&policy, performance);
+    if let Some(performance) = performance {
+      if direction.is_better(performance, best) {
+        best = performance;
+      }
+    }
+    curve.push(best);
+    if hit.is_none() && !direction.is_better(target, best) {
+      hit = Some(evaluation);
+    }
+  }
+  (curve, hit)
+}
+
+/// Linearly interpolated quantile `q` of sorted `values`.
+fn quantile(values: &[f64], q: f64) -> f64 {
+  let position: f64 = q.clamp(0_f64, 1_f64) * (values.len() - 1) as f64;
+  let low: usize = position.floor() as usize;
+  let high: usize = position.ceil() as usize;
+  values[low] + (position - low as f64) * (values[high] - values[low])
+}
+
+/// Runs every contender on `benchmark` once per seed, in parallel over seeds, and summarizes the best-so-far curves.
+pub fn compare(benchmark: &(dyn Benchmark + Sync), contenders: &[Contender], settings: &CompareSettings) -> Comparison {
+  assert!(settings.seeds > 0, "compare needs at least one seed");
+  assert!(settings.budget > 0, "compare needs a budget of at least one evaluation");
+  let direction: Direction = benchmark.direction();
+  let target: f64 = settings.target.unwrap_or(benchmark.optimum() - direction.sign() * 1e-2);
+  let reports: Vec<ContenderReport> = contenders
+  .iter()
+  .map(|contender| {
+    let runs: Vec<(Vec<f64>, Option<usize>)> = (0..settings.seeds)
+    .into_par_iter()
+    .map(|seed| {
+      let mut optimizer: Box<dyn Optimizer + Send> = (contender.build)(benchmark, settings.budget, seed);
+      run_once(benchmark, optimizer.as_mut(), settings.budget, target)
+    })
+    .collect::<Vec<(Vec<f64>, Option<usize>)>>();
+    let by_evaluation: Vec<Vec<f64>> = (0..settings.budget)
+    .map(|i| {
+      let mut values: Vec<f64> = runs.iter().map(|x| {x.0[i]}).collect::<Vec<f64>>();
+      values.sort_by(|a, b| {a.partial_cmp(b).unwrap()});
+      values
+    })
+    .collect::<Vec<Vec<f64>>>();
+    ContenderReport {
+      name: contender.name.clone(),
+      mean: by_evaluation
+      .iter()
+      .map(|x| {x.iter().sum::<f64>() / x.len() as f64})
+      .collect::<Vec<f64>>(),
+      quantiles: settings.quantiles
+      .iter()
+      .map(|q| {(*q, by_evaluation.iter().map(|x| {quantile(x, *q)}).collect::<Vec<f64>>())})
+      .collect::<Vec<(f64, Vec<f64>)>>(),
+      evaluations_to_target: runs.iter().map(|x| {x.1}).collect::<Vec<Option<usize>>>(),
+    }
+  })
+  .collect::<Vec<ContenderReport>>();
+  Comparison {
+    benchmark: benchmark.name().to_string(),
+    optimum: benchmark.optimum(),
+    target,
+    direction,
+    contenders: reports,
+  }
+}
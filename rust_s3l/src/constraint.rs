@@ -0,0 +1,40 @@
+use rand::Rng;
+
+use crate::agent::S3LAgent;
+
+/// A constraint known before evaluation, checked against parameter values.
+///
+/// Constraints only discovered by evaluating are reported by the objective instead, see
+/// [`Objective::try_evaluate`](crate::Objective::try_evaluate).
+pub trait Constraint {
+  fn is_feasible(&self, params: &[f64]) -> bool;
+}
+
+impl<F> Constraint for F
+where
+  F: Fn(&[f64]) -> bool,
+{
+  fn is_feasible(&self, params: &[f64]) -> bool {
+    self(params)
+  }
+}
+
+/// How many times `select_policy` re-proposes before giving up on satisfying the known constraints.
+pub const MAX_CONSTRAINT_RETRIES: u32 = 100;
+
+impl<R: Rng> S3LAgent<R> {
+  pub fn add_constraint<C: Constraint + Send + 'static>(&mut self, constraint: C) {
+    self.constraints.push(Box::new(constraint));
+  }
+
+  /// Whether a normalized policy satisfies every known constraint.
+  pub fn is_feasible(&self, policy: &[f64]) -> bool {
+    if self.constraints.is_empty() {
+      return true;
+    }
+    let params: Vec<f64> = self.space.decode(policy);
+    self.constraints
+    .iter()
+    .all(|x| {x.is_feasible(&params)})
+  }
+}
@@ -0,0 +1,135 @@
+use crate::metric::Metric;
+
+/// A k-d tree over points identified by their insertion index, built incrementally.
+///
+/// Nodes split on the axis of their depth modulo the dimension count. Insertion does not rebalance, which is fine
+/// for the scattered policies S3L proposes.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub(crate) struct KdTree {
+  nodes: Vec<KdNode>
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct KdNode {
+  point: Vec<f64>,
+  axis: usize,
+  left: Option<usize>,
+  right: Option<usize>
+}
+
+impl KdTree {
+  pub(crate) fn new() -> KdTree {
+    KdTree {
+      nodes: Vec::new(),
+    }
+  }
+
+  /// Adds a point; its id is the number of points inserted before it.
+  pub(crate) fn insert(&mut self, point: &[f64]) {
+    let id: usize = self.nodes.len();
+    let dims: usize = point.len().max(1);
+    let mut depth: usize = 0;
+    if id > 0 {
+      let mut current: usize = 0;
+      loop {
+        let node: &KdNode = &self.nodes[current];
+        let go_left: bool = point[node.axis] < node.point[node.axis];
+        let next: Option<usize> = if go_left {node.left} else {node.right};
+        depth += 1;
+        match next {
+          Some(next) => current = next,
+          None => {
+            if go_left {
+              self.nodes[current].left = Some(id);
+            } else {
+              self.nodes[current].right = Some(id);
+            }
+            break;
+          }
+        }
+      }
+    }
+    self.nodes.push(KdNode {
+      point: point.to_vec(),
+      axis: depth % dims,
+      left: None,
+      right: None,
+    });
+  }
+
+  /// The id and distance of the point closest to `target` under `metric` among those `accept` lets through.
+  ///
+  /// `stack` is working space; passing the same buffer to every query avoids allocating.
+  pub(crate) fn nearest<F: Fn(usize) -> bool>(&self, target: &[f64], metric: &dyn Metric, accept: F, stack: &mut Vec<(usize, f64)>) -> Option<(usize, f64)> {
+    let mut best: Option<(usize, f64)> = None;
+    // Each pending node comes with a lower bound on the distance of anything below it.
+    stack.clear();
+    if !self.nodes.is_empty() {
+      stack.push((0, 0_f64));
+    }
+    while let Some((current, bound)) = stack.pop() {
+      if best.is_some_and(|x| {bound >= x.1}) {
+        continue;
+      }
+      let node: &KdNode = &self.nodes[current];
+      if accept(current) {
+        let distance: f64 = metric.distance(&node.point, target);
+        if best.is_none_or(|x| {distance < x.1}) {
+          best = Some((current, distance));
+        }
+      }
+      let gap: f64 = target[node.axis] - node.point[node.axis];
+      let (near, far): (Option<usize>, Option<usize>) = if gap < 0_f64 {(node.left, node.right)} else {(node.right, node.left)};
+      // The far side can only hold a closer point if the splitting plane is within the best distance.
+      if let Some(far) = far {
+        stack.push((far, bound.max(metric.axis_lower_bound(node.axis, gap))));
+      }
+      if let Some(near) = near {
+        stack.push((near, bound));
+      }
+    }
+    best
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::rngs::StdRng;
+  use rand::{Rng, SeedableRng};
+
+  use crate::kdtree::KdTree;
+  use crate::metric::{Chebyshev, Euclidean, Mahalanobis, Manhattan, Metric, Periodic, WeightedEuclidean};
+
+  #[test]
+  fn nearest_matches_linear_scan() {
+    let metrics: Vec<Box<dyn Metric>> = vec![
+      Box::new(Euclidean),
+      Box::new(Manhattan),
+      Box::new(Chebyshev),
+      Box::new(WeightedEuclidean { weights: vec![0.5, 2_f64, 1_f64] }),
+      Box::new(Mahalanobis::new(vec![vec![1_f64, 0.6, 0_f64], vec![0.6, 2_f64, -0.3], vec![0_f64, -0.3, 0.5]])),
+      Box::new(Periodic { periodic: vec![true, false, true] }),
+    ];
+    let mut rng: StdRng = StdRng::seed_from_u64(5);
+    let points: Vec<Vec<f64>> = (0..500)
+    .map(|_x| {(0..3).map(|_y| {rng.gen::<f64>()}).collect::<Vec<f64>>()})
+    .collect::<Vec<Vec<f64>>>();
+    let mut tree: KdTree = KdTree::new();
+    for point in points.iter() {
+      tree.insert(point);
+    }
+    let accept = |x: usize| -> bool {!x.is_multiple_of(3)};
+    let mut stack: Vec<(usize, f64)> = Vec::new();
+    for metric in metrics.iter() {
+      for _query in 0..100 {
+        let target: Vec<f64> = (0..3).map(|_y| {rng.gen::<f64>()}).collect::<Vec<f64>>();
+        let expected: Option<(usize, f64)> = (0..points.len())
+        .filter(|x| {accept(*x)})
+        .map(|x| {(x, metric.distance(&points[x], &target))})
+        .min_by(|a, b| {a.1.total_cmp(&b.1)});
+        let found: Option<(usize, f64)> = tree.nearest(&target, metric.as_ref(), accept, &mut stack);
+        assert_eq!(found.map(|x| {x.1}), expected.map(|x| {x.1}));
+      }
+    }
+  }
+}
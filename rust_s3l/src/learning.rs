@@ -0,0 +1,127 @@
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::{Done, S3LAgent};
+use crate::objective::Objective;
+use crate::stop::{StopCriteria, StopReason};
+use crate::xi_table::XiEntry;
+
+/// Evaluates a policy and records the result, or records it as infeasible without evaluating when it violates a
+/// known constraint.
+fn evaluate_and_record<R: Rng, O: Objective + ?Sized>(agentmodel: &mut S3LAgent<R>, objective: &O, policy: Vec<f64>, params: &[f64]) -> Done {
+  let performance: Option<f64> = if agentmodel.is_feasible(&policy) {objective.try_evaluate(params)} else {None};
+  match performance {
+    Some(performance) => agentmodel.update_xi_table(policy, performance),
+    None => agentmodel.update_xi_table_infeasible(policy),
+  }
+}
+
+/// Selects a policy, evaluates it and records the result.
+pub fn real_learn_step<R: Rng, O: Objective + ?Sized>(agentmodel: &mut S3LAgent<R>, objective: &O) -> XiEntry {
+  let policy: Vec<f64> = agentmodel.select_policy();
+  let params: Vec<f64> = agentmodel.decode(&policy);
+  evaluate_and_record(agentmodel, objective, policy, &params);
+  agentmodel.xi_table().last().unwrap().clone()
+}
+
+/// Evaluates the next point of the initial design and records the result.
+pub fn pure_exploration_step<R: Rng, O: Objective + ?Sized>(agentmodel: &mut S3LAgent<R>, objective: &O) -> Done {
+  let policy: Vec<f64> = agentmodel.design_policy();
+  let params: Vec<f64> = agentmodel.decode(&policy);
+  agentmodel.observer.on_exploration(&params);
+  evaluate_and_record(agentmodel, objective, policy, &params)
+}
+
+/// Re-evaluates the entry chosen by [`S3LAgent::select_reevaluation`], returning it with its updated statistics.
+///
+/// A re-evaluation that turns out infeasible adds no sample.
+pub fn reevaluation_step<R: Rng, O: Objective + ?Sized>(agentmodel: &mut S3LAgent<R>, objective: &O) -> Option<XiEntry> {
+  let step: u64 = agentmodel.select_reevaluation()?;
+  let params: Vec<f64> = agentmodel.decode(&agentmodel.xi_table().entries()[step as usize].policy);
+  if let Some(performance) = objective.try_evaluate(&params) {
+    agentmodel.add_sample(step, performance);
+  }
+  Some(agentmodel.xi_table().entries()[step as usize].clone())
+}
+
+/// The outcome of [`learning_S3L`].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RunResult {
+  /// The best feasible entry in the xi table when the run stopped, if any.
+  pub best: Option<XiEntry>,
+  /// The best policy mapped to parameter values.
+  pub best_params: Option<Vec<f64>>,
+  /// Evaluations made during this run.
+  pub evaluations: usize,
+  pub elapsed: Duration,
+  pub stop_reason: StopReason,
+  /// The non-dominated entries of a multi-objective run, empty otherwise.
+  #[serde(default)]
+  pub pareto_front: Vec<XiEntry>,
+  /// Restarts the agent has made so far, see [`Restart`](crate::Restart).
+  #[serde(default)]
+  pub restarts: u32
+}
+
+/// Runs the agent, evaluating the initial design until the xi table holds the warm-up size, until `stop` fires.
+///
+/// With [`NoiseHandling`](crate::NoiseHandling), a re-evaluation is interleaved after every `reevaluate_every` new
+/// policies; it counts as an evaluation.
+#[allow(non_snake_case)]
+pub fn learning_S3L<R: Rng, O: Objective + ?Sized>(agentmodel: &mut S3LAgent<R>, objective: &O, stop: &StopCriteria) -> RunResult {
+  run_learning(
+    agentmodel,
+    stop,
+    |x| {pure_exploration_step(x, objective);},
+    |x| {real_learn_step(x, objective);},
+    |x| {reevaluation_step(x, objective).is_some()},
+  )
+}
+
+/// The loop behind every learning variant. `reevaluate` returns whether it made an evaluation.
+pub(crate) fn run_learning<R, E, L, V>(agentmodel: &mut S3LAgent<R>, stop: &StopCriteria, mut explore: E, mut learn: L, mut reevaluate: V) -> RunResult
+where
+  R: Rng,
+  E: FnMut(&mut S3LAgent<R>),
+  L: FnMut(&mut S3LAgent<R>),
+  V: FnMut(&mut S3LAgent<R>) -> bool,
+{
+  let started: Instant = Instant::now();
+  let mut best_history: Vec<f64> = Vec::new();
+  let mut stop_reason: Option<StopReason> = None;
+  let mut since_reevaluation: usize = 0;
+  while stop_reason.is_none() {
+    let reevaluate_every: Option<usize> = agentmodel.noise().map(|x| {x.reevaluate_every});
+    if agentmodel.in_warm_up() {
+      explore(agentmodel);
+    } else if reevaluate_every.is_some_and(|x| {since_reevaluation >= x}) && reevaluate(agentmodel) {
+      since_reevaluation = 0;
+    } else {
+      learn(agentmodel);
+      since_reevaluation += 1;
+    }
+    // Until a feasible policy is found the best is the worst possible performance.
+    let best_performance: f64 = match agentmodel.xi_table().best() {
+      Some(best) => best.performance,
+      None => -agentmodel.direction().sign() * f64::INFINITY,
+    };
+    best_history.push(best_performance);
+    agentmodel.save_checkpoint(best_history.len());
+    stop_reason = stop.check(&best_history, started.elapsed(), agentmodel.direction());
+  }
+  let best_xi_table_entry: Option<XiEntry> = agentmodel.xi_table().best().cloned();
+  let best_params: Option<Vec<f64>> = best_xi_table_entry.as_ref().map(|x| {agentmodel.decode(&x.policy)});
+  let result: RunResult = RunResult {
+    best: best_xi_table_entry,
+    best_params,
+    evaluations: best_history.len(),
+    elapsed: started.elapsed(),
+    stop_reason: stop_reason.unwrap(),
+    pareto_front: agentmodel.pareto_front().into_iter().cloned().collect::<Vec<XiEntry>>(),
+    restarts: agentmodel.restarts(),
+  };
+  agentmodel.observer.on_stop(&result);
+  result
+}
@@ -0,0 +1,60 @@
+//! S3L reinforcement learning, a derivative of PREPV.
+
+extern crate rand;
+
+mod agent;
+mod baseline;
+mod batch;
+mod benchmark;
+mod compare;
+mod constraint;
+mod kdtree;
+mod learning;
+mod metric;
+mod mip;
+mod noise;
+mod objective;
+mod observer;
+mod pareto;
+mod performance;
+mod persist;
+mod refine;
+mod restart;
+mod schedule;
+mod space;
+mod stop;
+mod trial;
+mod vector;
+mod warm_up;
+mod xi_table;
+
+pub use agent::{Done, S3LAgent, S3LAgentBuilder, MAX_AVOIDANCE_RETRIES, MIN_PENDING_SEPARATION};
+pub use baseline::{EvolutionStrategy, GridSearch, HillClimbing, NelderMead, Optimizer, RandomSearch};
+pub use batch::batch_learn_step;
+pub use benchmark::{benchmark_suite, Ackley, Benchmark, Branin, Griewank, Hartmann, Rastrigin, Rosenbrock, Schwefel, Sphere};
+pub use compare::{compare, Comparison, CompareSettings, Contender, ContenderReport};
+pub use constraint::{Constraint, MAX_CONSTRAINT_RETRIES};
+pub use learning::{learning_S3L, pure_exploration_step, real_learn_step, reevaluation_step, RunResult};
+pub use metric::{Chebyshev, Euclidean, Mahalanobis, Manhattan, Metric, Periodic, WeightedEuclidean};
+pub use mip::MipEstimator;
+pub use noise::NoiseHandling;
+pub use objective::{ExternalCommand, Objective, ObjectiveSpec, TwoPeak};
+pub use observer::{HumanObserver, JsonLinesObserver, Observer, QuietObserver};
+pub use pareto::{
+  learning_S3L_pareto, pareto_exploration_step, pareto_learn_step, MultiObjective, ParetoRanking, ParetoSettings,
+  Scalarization
+};
+pub use performance::{Direction, Normalization};
+pub use persist::{Checkpoint, Format, FORMAT_VERSION};
+pub use refine::Refinement;
+pub use restart::Restart;
+pub use schedule::Schedule;
+pub use space::{Param, SearchSpace};
+pub use stop::{StopCriteria, StopReason};
+pub use trial::Trial;
+pub use vector::{
+  get_l2_n_dist, sub_vectors, sub_vectors_into, sum_vectors, sum_vectors_in_place, vector_scalar_multiply,
+  vector_scalar_multiply_in_place
+};
+pub use warm_up::{Sampling, WarmUp, MAX_SOBOL_DIMS};
+pub use xi_table::{XiEntry, XiTable};
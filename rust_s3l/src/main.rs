@@ -1,321 +1,612 @@
-extern crate rand;
+extern crate rust_s3l;
 
-use rand::Rng;
-use rand::StdRng;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Duration;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use rand::rngs::StdRng;
 use rand::SeedableRng;
-use std::mem::drop;
+use rust_s3l::{
+  compare, learning_S3L, Ackley, Benchmark, Branin, Checkpoint, Comparison, CompareSettings, Contender, Direction,
+  ExternalCommand, Format, Griewank, Hartmann, HumanObserver, JsonLinesObserver, MipEstimator, NoiseHandling,
+  Normalization, Objective, ObjectiveSpec, Observer, QuietObserver, Rastrigin, Refinement, Restart, Rosenbrock,
+  S3LAgent, S3LAgentBuilder, Sampling, Schedule, Schwefel, Sphere, StopCriteria, TwoPeak, WarmUp, XiEntry,
+  MAX_SOBOL_DIMS
+};
 
-fn main() {
-  learning_S3L();
+#[derive(Parser)]
+#[command(name = "rust_s3l", about = "Run S3L reinforcement learning experiments.")]
+struct Cli {
+  /// How `run` and `resume` report progress.
+  #[arg(long, global = true, value_enum, default_value_t = LogArg::Human)]
+  log: LogArg,
+  #[command(subcommand)]
+  command: CliCommand
 }
 
-fn gen_random_f64() -> f64 {
-  
-  let seed: &[_] = &[time_seed];
-  let mut rng: rand::prelude::ThreadRng = rand::thread_rng();
-  rng.gen::<f64>()
+#[derive(Clone, Copy, ValueEnum)]
+enum LogArg {
+  Quiet,
+  Human,
+  /// One JSON object per event on stdout.
+  Json
 }
 
-struct S3LAgent {
-  dims: u32,
-  mip: f64,
-  j: u32,
-  xi_table: Vec<Vec<Vec<f64>>>,
-  upsilon: f64
+impl LogArg {
+  fn observer(self) -> Box<dyn Observer + Send> {
+    match self {
+      LogArg::Quiet => Box::new(QuietObserver),
+      LogArg::Human => Box::new(HumanObserver),
+      LogArg::Json => Box::new(JsonLinesObserver::new(io::stdout())),
+    }
+  }
 }
 
-fn build_s3l_agent(dims: u32, mip: f64, j: u32) -> S3LAgent {
-  let initial_xi_table: Vec<Vec<Vec<f64>>> = Vec::new();
-  let initial_upsilon: f64 = 0 as f64;
-  S3LAgent {
-    dims: dims,
-    mip: mip,
-    j: j,
-    xi_table: initial_xi_table,
-    upsilon: initial_upsilon,
+#[derive(Subcommand)]
+enum CliCommand {
+  /// Start a new run.
+  Run {
+    /// Defaults to 2, or to the dimensions of a fixed-size benchmark.
+    #[arg(long)]
+    dims: Option<u32>,
+    /// Maximum ideal performance, or the initial guess when it is estimated. Defaults to the known optimum of a
+    /// built-in objective and is required for external ones.
+    #[arg(long, allow_hyphen_values = true)]
+    mip: Option<f64>,
+    /// Estimate mip online instead of trusting --mip.
+    #[arg(long, value_enum)]
+    estimate_mip: Option<MipEstimatorArg>,
+    /// Treat lower performance as better; --mip is then the ideal lowest performance. Built-in objectives already
+    /// know their direction, and one that is maximized rejects this flag.
+    #[arg(long)]
+    minimize: bool,
+    /// Defaults to ratio when maximizing toward a positive --mip and to min-max otherwise, or to what a built-in
+    /// objective needs.
+    #[arg(long, value_enum)]
+    normalization: Option<NormalizationArg>,
+    /// Treat the objective as noisy, ranking by mean minus this many standard errors and re-evaluating the best.
+    #[arg(long)]
+    noise_kappa: Option<f64>,
+    #[arg(long, default_value_t = 2)]
+    j: u32,
+    /// How the exponent moves away from --j during the run.
+    #[arg(long, value_enum, default_value_t = ScheduleArg::Constant)]
+    schedule: ScheduleArg,
+    /// Exponent an annealed schedule ends at.
+    #[arg(long, default_value_t = 1_f64)]
+    j_final: f64,
+    /// Evaluations after the warm-up an annealed schedule takes to reach --j-final.
+    #[arg(long, default_value_t = 100)]
+    anneal_steps: u64,
+    /// Probe around the best policy once upsilon reaches this.
+    #[arg(long)]
+    refine_threshold: Option<f64>,
+    /// Most probes in one refinement phase.
+    #[arg(long, default_value_t = 50)]
+    refine_budget: usize,
+    /// Start a new epoch when the agent stagnates.
+    #[arg(long, value_enum)]
+    restart: Option<RestartArg>,
+    /// Evaluations without a new best before a restart.
+    #[arg(long, default_value_t = 50)]
+    restart_patience: usize,
+    /// How much each IPOP restart grows the warm-up and the patience.
+    #[arg(long, default_value_t = 2_f64)]
+    ipop_growth: f64,
+    /// Evaluations in the initial design, before S3L starts steering.
+    #[arg(long, default_value_t = 3)]
+    warm_up: usize,
+    /// Extra initial design evaluations per dimension.
+    #[arg(long, default_value_t = 0)]
+    warm_up_per_dim: usize,
+    #[arg(long, value_enum, default_value_t = SamplingArg::Uniform)]
+    sampling: SamplingArg,
+    #[arg(long)]
+    seed: Option<u64>,
+    #[command(flatten)]
+    budget: BudgetArgs,
+    #[command(flatten)]
+    objective: ObjectiveArgs,
+    #[command(flatten)]
+    save: SaveArgs
+  },
+  /// Continue a saved run on the objective it was saved with.
+  Resume {
+    state: PathBuf,
+    #[arg(long)]
+    seed: Option<u64>,
+    #[command(flatten)]
+    budget: BudgetArgs,
+    #[command(flatten)]
+    objective: ObjectiveArgs,
+    #[command(flatten)]
+    save: SaveArgs
+  },
+  /// Summarize a saved run.
+  Inspect {
+    state: PathBuf
+  },
+  /// Run S3L and the baseline optimizers on a built-in objective over many seeds.
+  Compare {
+    #[arg(long, value_enum, default_value_t = ObjectiveArg::TwoPeak)]
+    objective: ObjectiveArg,
+    /// Defaults to 2, or to the dimensions of a fixed-size benchmark.
+    #[arg(long)]
+    dims: Option<u32>,
+    /// Evaluations per run.
+    #[arg(long, default_value_t = 100)]
+    budget: usize,
+    #[arg(long, default_value_t = 20)]
+    seeds: u64,
+    /// Performance counting as success; defaults to within 0.01 of the optimum.
+    #[arg(long, allow_hyphen_values = true)]
+    target: Option<f64>,
+    /// Where to write the best-so-far curves as CSV.
+    #[arg(long)]
+    output: Option<PathBuf>
+  },
+  /// Write a saved run's xi table as CSV.
+  Export {
+    state: PathBuf,
+    /// Defaults to stdout.
+    #[arg(long)]
+    output: Option<PathBuf>
   }
 }
 
-impl S3LAgent {
-  fn update_xi_table(&mut self, policy: Vec<f64>, performance: f64) -> Done {
-    let performance_vec: Vec<f64> = vec![performance];
-    let new_entry: Vec<Vec<f64>> = vec![policy, performance_vec];
-    self.xi_table.push(new_entry);
-    Done
-  }
+#[derive(Args)]
+struct BudgetArgs {
+  /// Maximum number of evaluations in this run.
+  #[arg(long, default_value_t = 100)]
+  budget: usize,
+  /// Stop once this performance is reached.
+  #[arg(long, allow_hyphen_values = true)]
+  target: Option<f64>,
+  /// Wall-clock budget in seconds.
+  #[arg(long)]
+  time_budget: Option<f64>,
+  /// Stop after this many evaluations without a new best.
+  #[arg(long)]
+  patience: Option<usize>
+}
 
-  fn select_policy(&mut self) -> Vec<f64> {
-    self.calculate_upsilon();
-    let mut initial_point: Option<Vec<f64>> = None;
-    if self.xi_table.len() > 3 {
-      initial_point = Some(self.generate_avoidance_point());
-    } else {
-      let initial_point_unwrapped = (0..(self.dims as u32))
-      .collect::<Vec<u32>>()
-      .iter()
-      .map(|_x| {gen_random_f64()})
-      .collect::<Vec<f64>>();
-      initial_point = Some(initial_point_unwrapped);
+impl BudgetArgs {
+  fn stop_criteria(&self) -> StopCriteria {
+    let mut stop: StopCriteria = StopCriteria::new().max_evaluations(self.budget);
+    if let Some(target) = self.target {
+      stop = stop.target_performance(target);
     }
-    let probabilistic_max: Vec<f64> = self.generate_probabilistic_max();
-    let vector_delta: Vec<f64> = self.sub_vectors(initial_point.unwrap(), probabilistic_max);
-    let scalar: f64 = self.get_vector_delta_scalar();
-    let modified_vector_delta: Vec<f64> = self.vector_scalar_multiply(vector_delta, scalar);
-    let result: Vec<f64> = self.sum_vectors(initial_point.unwrap(), modified_vector_delta);
-    result
-  }
-
-  fn calculate_upsilon(&mut self) -> Done {
-    let upsilon_result: Option<f64> = None;
-    if self.xi_table.len() > 3 {
-      let xi_table_max_performance: f64 = self.xi_table
-      .iter()
-      .fold(std::f64::NEG_INFINITY, |a, x| -> f64 {if x[1][0] > a {x[1][0]} else {a}});
-      let intial_result: f64 = xi_table_max_performance / self.mip;
-      if intial_result >= 0.8 {
-        let base: f64 = 5_f64 * (intial_result - 0.8);
-        upsilon_result = Some(0.8 + ((base.powf(self.j as f64)) / 5_f64));
-      } else {
-        upsilon_result = Some(intial_result);
-      }
-    } else {
-      upsilon_result = Some(0_f64);
+    if let Some(time_budget) = self.time_budget {
+      stop = stop.time_budget(Duration::from_secs_f64(time_budget));
+    }
+    if let Some(patience) = self.patience {
+      stop = stop.patience(patience);
     }
-    self.upsilon = upsilon_result.unwrap();
-    Done
+    stop
   }
+}
 
-  fn generate_avoidance_point(&self) -> Vec<f64> {
-    let initial_vector: Vec<f64> = (0..(self.dims as u32))
-    .collect::<Vec<u32>>()
-    .iter()
-    .map(|_x| {gen_random_f64()})
-    .collect::<Vec<f64>>();
-    let i_avg_xi_table: f64 = (3_f64 / 4_f64) * self.mip;
-    let possible_mins: Vec<Vec<Vec<f64>>> = self.xi_table
-    .iter()
-    .filter(|x| {x[1][0] < i_avg_xi_table})
-    .collect::<Vec<&Vec<Vec<f64>>>>()
-    .iter()
-    .map(|x| {(*x).clone().to_vec()})
-    .collect::<Vec<Vec<Vec<f64>>>>();
-    let possible_maxs: Vec<Vec<Vec<f64>>> = self.xi_table
-    .iter()
-    .filter(|x| {x[1][0] >= i_avg_xi_table})
-    .collect::<Vec<&Vec<Vec<f64>>>>()
-    .iter()
-    .map(|x| {(*x).clone().to_vec()})
-    .collect::<Vec<Vec<Vec<f64>>>>();
-    let possible_min_vectors: Vec<Vec<f64>> = possible_mins
-    .iter()
-    .map(|x| {x[0]})
-    .collect::<Vec<Vec<f64>>>();
-    let possible_max_vectors: Vec<Vec<f64>> = possible_maxs
-    .iter()
-    .map(|x| {x[0]})
-    .collect::<Vec<Vec<f64>>>();
-    let mut closest_min: Vec<f64> = possible_min_vectors[0];
-    let mut best_min_dist: f64 = std::f64::INFINITY;
-    let mut dist: Option<f64> = None;
-    for i in possible_min_vectors.iter() {
-      dist = Some(self.get_l2_n_dist(closest_min, initial_vector));
-      if dist.unwrap() < best_min_dist {
-        best_min_dist = dist.unwrap();
-        closest_min = i.clone();
-      }
+#[derive(Clone, Copy, ValueEnum)]
+enum ObjectiveArg {
+  TwoPeak,
+  Sphere,
+  Rastrigin,
+  Rosenbrock,
+  Ackley,
+  Griewank,
+  Schwefel,
+  Branin,
+  Hartmann3,
+  Hartmann6
+}
+
+#[derive(Args)]
+struct ObjectiveArgs {
+  /// Built-in objective, used when no command is given. A new run defaults to two-peak, and a resumed run to the
+  /// objective it was saved with.
+  #[arg(long, value_enum)]
+  objective: Option<ObjectiveArg>,
+  /// External program to tune: it reads the parameters on stdin and prints the performance on stdout.
+  #[arg(last = true)]
+  command: Vec<String>
+}
+
+impl ObjectiveArg {
+  /// Scalable benchmarks take `dims`, 2 by default.
+  fn benchmark(self, dims: Option<u32>) -> io::Result<Box<dyn Benchmark + Send + Sync>> {
+    let scalable: u32 = dims.unwrap_or(2);
+    let benchmark: Box<dyn Benchmark + Send + Sync> = match self {
+      ObjectiveArg::TwoPeak => Box::new(TwoPeak),
+      ObjectiveArg::Sphere => Box::new(Sphere { dims: scalable }),
+      ObjectiveArg::Rastrigin => Box::new(Rastrigin { dims: scalable }),
+      ObjectiveArg::Rosenbrock => Box::new(Rosenbrock { dims: scalable }),
+      ObjectiveArg::Ackley => Box::new(Ackley { dims: scalable }),
+      ObjectiveArg::Griewank => Box::new(Griewank { dims: scalable }),
+      ObjectiveArg::Schwefel => Box::new(Schwefel { dims: scalable }),
+      ObjectiveArg::Branin => Box::new(Branin),
+      ObjectiveArg::Hartmann3 => Box::new(Hartmann::Three),
+      ObjectiveArg::Hartmann6 => Box::new(Hartmann::Six),
+    };
+    if dims.is_some_and(|x| {x != benchmark.dims()}) {
+      let message: String = format!("{} is {}-dimensional", benchmark.name(), benchmark.dims());
+      return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
     }
-    assert!(dist != None);
-    let mut closest_max: Vec<f64> = possible_max_vectors[0];
-    let mut best_max_dist: f64 = std::f64::INFINITY;
-    for i in possible_max_vectors.iter() {
-      dist = Some(self.get_l2_n_dist(closest_max, initial_vector));
-      if dist.unwrap() < best_max_dist {
-        best_max_dist = dist.unwrap();
-        closest_max = i.clone();
-      }
+    if benchmark.dims() < 2 && matches!(self, ObjectiveArg::Rosenbrock) {
+      return Err(io::Error::new(io::ErrorKind::InvalidInput, "rosenbrock needs at least 2 dimensions"));
     }
-    assert!(dist != None);
-    drop(best_max_dist);
-    drop(best_min_dist);
-    let ddelta: f64 = self.get_l2_n_dist(closest_min, closest_max);
-    let dmin: f64 = self.get_l2_n_dist(initial_vector, closest_min);
-    let r: f64 = dmin / ddelta;
-    let mut tryagain: Option<bool> = None;
-    if r < 0.5 {
-      tryagain = Some(gen_random_f64() <= (r * self.upsilon));
-    } else {
-      tryagain = Some(false);
+    Ok(benchmark)
+  }
+}
+
+impl ObjectiveArgs {
+  /// The built-in objective of a new run, unless a command is given.
+  fn benchmark(&self, dims: Option<u32>) -> io::Result<Option<Box<dyn Benchmark + Send + Sync>>> {
+    if !self.command.is_empty() {
+      return Ok(None);
     }
-    let result: Option<Vec<f64>> = None;
-    if tryagain.unwrap() {
-      drop(tryagain);
-      result = Some(self.generate_avoidance_point());
-    } else {
-      result = Some(initial_vector);
+    Ok(Some(self.objective.unwrap_or(ObjectiveArg::TwoPeak).benchmark(dims)?))
+  }
+
+  /// The command, or else the built-in objective, if either was given.
+  fn spec(&self) -> Option<ObjectiveSpec> {
+    if let Some((program, args)) = self.command.split_first() {
+      return Some(ObjectiveSpec::Command(ExternalCommand::new(program.clone(), args.to_vec())));
     }
-    result.unwrap()
+    let name: String = self.objective?.to_possible_value()?.get_name().to_string();
+    Some(ObjectiveSpec::Benchmark(name))
   }
 
-  fn generate_probabilistic_max(&self) -> Vec<f64> {
-    let ls: &Vec<Vec<Vec<f64>>> = &((self.xi_table).clone());
-    ls.sort_by(|a, b| {a[1][0].partial_cmp(&b[1][0]).unwrap()});
-    let ranking: Vec<Vec<Vec<f64>>> = ls.to_vec().clone();
-    let initial_rr: Vec<Vec<Vec<f64>>> = ranking
-    .iter()
-    .rev()
-    .collect::<Vec<&Vec<Vec<f64>>>>()
-    .iter()
-    .map(|x| {(*x).clone().to_vec()})
-    .collect::<Vec<Vec<Vec<f64>>>>();
-    let rr = initial_rr.clone();
-    for i in 0..(rr.len() - 1) {
-      if gen_random_f64() < (self.upsilon / 2_f64) {
-        let tmp: Vec<Vec<f64>> = (rr[i + 1]).clone();
-        rr[i + 1] = (rr[i]).clone();
-        rr[i] = tmp.clone();
-        drop(tmp);
+  /// The objective of a resumed run: the one it was saved with, which an explicitly given one must match.
+  fn resumed_spec(&self, saved: Option<&ObjectiveSpec>) -> io::Result<ObjectiveSpec> {
+    match (self.spec(), saved) {
+      (Some(given), Some(saved)) if given != *saved => {
+        let message: String = format!("the run was saved optimizing {}, not {}", describe(saved), describe(&given));
+        Err(io::Error::new(io::ErrorKind::InvalidInput, message))
       }
+      (given, saved) => given.or_else(|| {saved.cloned()}).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "the saved run does not record its objective, pass --objective or a command")
+      }),
     }
-    let newranking: Vec<Vec<Vec<f64>>> = rr.clone();
-    let newtop: Vec<f64> = newranking[0][0];
-    newtop.clone()
   }
+}
 
-  fn get_vector_delta_scalar(&self) -> f64 {
-    (self.upsilon).powf(self.j as f64)
+fn check_dims(dims: Option<u32>) -> io::Result<()> {
+  if dims == Some(0) {
+    return Err(io::Error::new(io::ErrorKind::InvalidInput, "--dims must be positive"));
   }
+  Ok(())
+}
 
-  fn sub_vectors(&self, a: Vec<f64>, b: Vec<f64>) -> Vec<f64> {
-    let region_point: Vec<f64> = b.clone();
-    let d_range: Vec<usize> = (0..(self.dims))
-    .into_iter()
-    .map(|x: u32| -> usize {x as usize})
-    .collect::<Vec<usize>>();
-    let deltas: Vec<f64> = d_range
-    .iter()
-    .map(|x: &usize| -> f64 {region_point[*x] - a[*x]})
-    .collect::<Vec<f64>>();
-    drop(d_range);
-    deltas
+fn describe(spec: &ObjectiveSpec) -> String {
+  match spec {
+    ObjectiveSpec::Benchmark(name) => name.clone(),
+    ObjectiveSpec::Command(command) => format!("{} {}", command.program, command.args.join(" ")).trim_end().to_string(),
   }
+}
 
-  fn vector_scalar_multiply(&self, m_vector: Vec<f64>, m_scalar: f64) -> Vec<f64> {
-    m_vector
-    .iter()
-    .map(|x| {x * m_scalar})
-    .collect::<Vec<f64>>()
+/// Builds a recorded objective. A failed run of a command is reported on stderr and recorded as infeasible.
+fn build_objective(spec: &ObjectiveSpec, dims: u32) -> io::Result<Box<dyn Objective>> {
+  match spec {
+    ObjectiveSpec::Benchmark(name) => {
+      let objective: ObjectiveArg = ObjectiveArg::from_str(name, false).map_err(|_e| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("unknown objective {}", name))
+      })?;
+      Ok(objective.benchmark(Some(dims))?)
+    }
+    ObjectiveSpec::Command(command) => {
+      let command: ExternalCommand = command.clone();
+      Ok(Box::new(move |params: &[f64]| -> f64 {
+        command.run(params).unwrap_or_else(|e| {
+          eprintln!("warning: {}, treating the policy as infeasible", e);
+          f64::NAN
+        })
+      }))
+    }
   }
+}
 
-  fn sum_vectors(&self, a: Vec<f64>, b: Vec<f64>) -> Vec<f64> {
-    let d_range: Vec<usize> = (0..(self.dims))
-    .into_iter()
-    .map(|x: u32| -> usize {x as usize})
-    .collect::<Vec<usize>>();
-    let sum_result: Vec<f64> = d_range
-    .iter()
-    .map(|x: &usize| -> f64 {a[*x] + b[*x]})
-    .collect::<Vec<f64>>();
-    drop(d_range);
-    sum_result
-  }
+#[derive(Clone, Copy, ValueEnum)]
+enum MipEstimatorArg {
+  /// Best performance plus the mean spacing of the ten best.
+  ExtremeValue,
+  /// Best performance plus one standard deviation.
+  Optimistic
+}
 
-  fn get_l2_n_dist(&self, a: Vec<f64>, b: Vec<f64>) -> f64 {
-    let z = a
-    .iter()
-    .zip(b.iter());
-    let dist_sum: f64 = z
-    .into_iter()
-    .collect::<Vec<(&f64,&f64)>>()
-    .iter()
-    .fold(0_f64, |a, x| {a + (((*x).0 - (*x).1).powf(2_f64))});
-    let dist: f64 = dist_sum.sqrt();
-    dist
+impl MipEstimatorArg {
+  fn estimator(self) -> MipEstimator {
+    match self {
+      MipEstimatorArg::ExtremeValue => MipEstimator::ExtremeValue { k: 10 },
+      MipEstimatorArg::Optimistic => MipEstimator::OptimisticBound { kappa: 1_f64 },
+    }
   }
 }
 
-struct Done;
+#[derive(Clone, Copy, ValueEnum)]
+enum NormalizationArg {
+  /// Performance relative to mip; needs a positive mip and positive performances.
+  Ratio,
+  /// Position between the worst performance seen and mip; works for any values.
+  MinMax
+}
 
-fn real_learn_step(agentmodel: S3LAgent) -> Vec<Vec<f64>> {
-  let policy: Vec<f64> = agentmodel.select_policy();
-  let performance: f64 = evaluate_policy(policy);
-  agentmodel.update_xi_table(policy, performance);
-  println!("Agent tried policy {:?}. ", policy);
-  println!("This lead to performance {:?}. ", performance);
-  let current_performance_vec: Vec<f64> = vec![performance];
-  let pp_vec: Vec<Vec<f64>> = vec![policy, current_performance_vec];
-  pp_vec
+#[derive(Clone, Copy, ValueEnum)]
+enum ScheduleArg {
+  Constant,
+  /// Linearly from --j to --j-final.
+  Annealed,
+  /// Raise the exponent while over a fifth of recent evaluations find a new best, lower it otherwise.
+  SuccessRule
 }
 
-fn evaluate_policy(policy: Vec<f64>) -> f64 {
-  let real_max: Vec<f64> = vec![0.7, 0.7];
-  let real_max_dist: f64 = get_l2_n_dist(policy, real_max);
-  let initial: f64 = (2_f64 - real_max_dist) + 1_f64;
-  let local_max: Vec<f64> = vec![0.2,0.2];
-  let local_max_dist: f64 = get_l2_n_dist(policy, local_max);
-  let other_inital: f64 = 2_f64 - local_max_dist;
-  let mut policy_result: Option<f64> = None;
-  if real_max_dist < local_max_dist {
-    policy_result = Some(initial);
-  } else {
-    policy_result = Some(other_inital);
-  }
-  policy_result.unwrap()
+#[derive(Clone, Copy, ValueEnum)]
+enum RestartArg {
+  Stagnation,
+  /// Stagnation restarts with a growing warm-up.
+  Ipop
 }
 
-fn get_l2_n_dist(a: Vec<f64>, b: Vec<f64>) -> f64 {
-  let z = a
-  .iter()
-  .zip(b.iter());
-  let dist_sum: f64 = z
-  .into_iter()
-  .collect::<Vec<(&f64,&f64)>>()
-  .iter()
-  .fold(0_f64, |a, x| {a + (((*x).0 - (*x).1).powf(2_f64))});
-  let dist: f64 = dist_sum.sqrt();
-  dist
+#[derive(Clone, Copy, ValueEnum)]
+enum SamplingArg {
+  Uniform,
+  /// Latin hypercube.
+  Lhs,
+  Sobol,
+  Halton
 }
 
-fn pure_exploration_step(agentmodel: S3LAgent) -> Done {
-  let policy = (0..(agentmodel.dims as u32))
-  .collect::<Vec<u32>>()
-  .iter()
-  .map(|_x| {gen_random_f64()})
-  .collect::<Vec<f64>>();
-  let performance = evaluate_policy(policy);
-  agentmodel.update_xi_table(policy, performance);
-  println!("Agent explored policy {:?}. ", policy);
-  Done
+#[derive(Clone, Copy, ValueEnum)]
+enum FormatArg {
+  Json,
+  Binary
+}
+
+#[derive(Args)]
+struct SaveArgs {
+  /// Where to save the agent when the run stops.
+  #[arg(long)]
+  save: Option<PathBuf>,
+  /// Encoding of saved files; defaults to JSON for new runs and to the loaded file's format when resuming.
+  #[arg(long, value_enum)]
+  format: Option<FormatArg>,
+  /// Also save every this many evaluations during the run; 0 only saves at the end.
+  #[arg(long, default_value_t = 10)]
+  checkpoint_every: usize
 }
 
-fn learning_S3L() -> Done {
-  let mut agentmodel: S3LAgent = build_s3l_agent(2, 3.1, 2);
-  for _i in 0..3 {
-    pure_exploration_step(agentmodel);
+impl SaveArgs {
+  fn format(&self, default: Format) -> Format {
+    match self.format {
+      Some(FormatArg::Json) => Format::Json,
+      Some(FormatArg::Binary) => Format::Binary,
+      None => default,
+    }
+  }
+
+  fn checkpoint(&self, path: &Path, default: Format) -> Checkpoint {
+    Checkpoint { path: path.to_path_buf(), format: self.format(default), every: self.checkpoint_every }
+  }
+}
+
+fn main() {
+  let cli: Cli = Cli::parse();
+  if let Err(e) = run_cli(cli) {
+    eprintln!("error: {}", e);
+    process::exit(1);
   }
-  let mut not_done: bool = true;
-  let mut cnt: u32 = 2;
-  let mut best_policy: Vec<f64> = Vec::new();
-  let mut best_performance: f64 = std::f64::NEG_INFINITY;
-  let mut best_performance_vec: Vec<f64> = vec![best_performance];
-  let mut best_xi_table_entry: Vec<Vec<f64>> = vec![best_policy, best_performance_vec];
-  while not_done {
-    real_learn_step(agentmodel);
-    best_xi_table_entry = agentmodel.xi_table
-    .clone()
-    .iter()
-    .max_by(|a, b| {a[1][0].partial_cmp(&b[1][0]).unwrap()})
-    .unwrap()
-    .to_vec()
-    .clone();
-    best_policy = best_xi_table_entry[0];
-    best_performance = best_xi_table_entry[1][0];
-    not_done = best_performance < 2.9;
-    println!("Done state is {:?}. ", !not_done);
-    println!("{:?} steps have passed. ", cnt);
-    cnt = cnt + 1;
+}
+
+fn run_cli(cli: Cli) -> io::Result<()> {
+  match cli.command {
+    CliCommand::Run {
+      dims, mip, estimate_mip, minimize, normalization, noise_kappa, j, schedule, j_final, anneal_steps,
+      refine_threshold, refine_budget, restart, restart_patience, ipop_growth, warm_up, warm_up_per_dim, sampling, seed,
+      budget, objective, save
+    } => {
+      check_dims(dims)?;
+      let sampling: Sampling = match sampling {
+        SamplingArg::Uniform => Sampling::Uniform,
+        SamplingArg::Lhs => Sampling::LatinHypercube,
+        SamplingArg::Sobol => Sampling::Sobol,
+        SamplingArg::Halton => Sampling::Halton,
+      };
+      let benchmark: Option<Box<dyn Benchmark + Send + Sync>> = objective.benchmark(dims)?;
+      let direction: Direction = match &benchmark {
+        Some(benchmark) if minimize && benchmark.direction() == Direction::Maximize => {
+          let message: String = format!("{} is maximized, --minimize only applies to commands", benchmark.name());
+          return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+        }
+        Some(benchmark) => benchmark.direction(),
+        None if minimize => Direction::Minimize,
+        None => Direction::Maximize,
+      };
+      let run_dims: u32 = benchmark.as_ref().map_or(dims.unwrap_or(2), |x| {x.dims()});
+      if sampling == Sampling::Sobol && run_dims > MAX_SOBOL_DIMS {
+        let message: String = format!("--sampling sobol supports up to {} dimensions", MAX_SOBOL_DIMS);
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+      }
+      let mut builder: S3LAgentBuilder = match &benchmark {
+        Some(benchmark) => S3LAgent::benchmark_builder(&**benchmark),
+        None => {
+          let mip: f64 = mip.ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "--mip is required for external objectives")
+          })?;
+          S3LAgent::builder(dims.unwrap_or(2), mip).direction(direction).objective(objective.spec().unwrap())
+        }
+      };
+      if let Some(mip) = mip {
+        // A benchmark's normalization suits its own optimum, so it is re-derived for the overriding mip.
+        builder = builder.mip(mip).normalization(Normalization::default_for(direction, mip));
+      }
+      if let Some(normalization) = normalization {
+        let ideal: f64 = mip.or(benchmark.as_ref().map(|x| {x.optimum()})).unwrap();
+        if matches!(normalization, NormalizationArg::Ratio) && ideal <= 0_f64 {
+          return Err(io::Error::new(io::ErrorKind::InvalidInput, "--normalization ratio needs a positive mip"));
+        }
+        builder = builder.normalization(match normalization {
+          NormalizationArg::Ratio => Normalization::Ratio,
+          NormalizationArg::MinMax => Normalization::MinMax,
+        });
+      }
+      let schedule: Schedule = match schedule {
+        ScheduleArg::Constant => Schedule::Constant,
+        ScheduleArg::Annealed => Schedule::Annealed { start: j as f64, end: j_final, steps: anneal_steps },
+        ScheduleArg::SuccessRule => Schedule::success_rule(),
+      };
+      builder = builder
+      .j(j)
+      .schedule(schedule)
+      .warm_up(WarmUp { base: warm_up, per_dim: warm_up_per_dim, sampling })
+      .observer(cli.log.observer());
+      if let Some(seed) = seed {
+        builder = builder.seed(seed);
+      }
+      if let Some(threshold) = refine_threshold {
+        builder = builder.refinement(Refinement { threshold, budget: refine_budget, ..Refinement::default() });
+      }
+      if let Some(restart) = restart {
+        builder = builder.restart(match restart {
+          RestartArg::Stagnation => Restart::Stagnation { patience: restart_patience },
+          RestartArg::Ipop => Restart::Ipop { patience: restart_patience, growth: ipop_growth },
+        });
+      }
+      if let Some(kappa) = noise_kappa {
+        builder = builder.noise(NoiseHandling { kappa, ..NoiseHandling::default() });
+      }
+      if let Some(estimate_mip) = estimate_mip {
+        builder = builder.estimate_mip(estimate_mip.estimator());
+      }
+      if let Some(path) = &save.save {
+        builder = builder.checkpoint(save.checkpoint(path, Format::Json));
+      }
+      let mut agentmodel: S3LAgent = builder.build();
+      let objective: Box<dyn Objective> = build_objective(agentmodel.objective().unwrap(), agentmodel.dims())?;
+      learning_S3L(&mut agentmodel, &*objective, &budget.stop_criteria());
+      if let Some(path) = &save.save {
+        agentmodel.save(path, save.format(Format::Json))?;
+      }
+      Ok(())
+    }
+    CliCommand::Resume { state, seed, budget, objective, save } => {
+      let mut agentmodel: S3LAgent = match seed {
+        Some(seed) => S3LAgent::load_with_rng(&state, StdRng::seed_from_u64(seed))?,
+        None => S3LAgent::load(&state)?,
+      };
+      agentmodel.set_observer(cli.log.observer());
+      let format: Format = save.format(Format::of_file(&state)?);
+      let path: PathBuf = save.save.clone().unwrap_or_else(|| {state.clone()});
+      agentmodel.set_checkpoint(Some(save.checkpoint(&path, format)));
+      let spec: ObjectiveSpec = objective.resumed_spec(agentmodel.objective())?;
+      let objective: Box<dyn Objective> = build_objective(&spec, agentmodel.dims())?;
+      agentmodel.set_objective(Some(spec));
+      learning_S3L(&mut agentmodel, &*objective, &budget.stop_criteria());
+      agentmodel.save(&path, format)
+    }
+    CliCommand::Inspect { state } => {
+      let agentmodel: S3LAgent = S3LAgent::load(&state)?;
+      if let Some(objective) = agentmodel.objective() {
+        println!("objective: {}", describe(objective));
+      }
+      println!("dims: {}", agentmodel.dims());
+      match agentmodel.mip_estimator() {
+        Some(estimator) => println!("mip: {} (estimated with {:?})", agentmodel.mip(), estimator),
+        None => println!("mip: {}", agentmodel.mip()),
+      }
+      println!("direction: {:?}", agentmodel.direction());
+      println!("normalization: {:?}", agentmodel.normalization());
+      match agentmodel.schedule() {
+        Schedule::Constant => println!("j: {}", agentmodel.j()),
+        schedule => println!("j: {} (now {} with {:?})", agentmodel.j(), agentmodel.exponent(), schedule),
+      }
+      println!("warm-up: {} evaluations, {:?} sampling", agentmodel.warm_up_size(), agentmodel.warm_up().sampling);
+      if let Some(refinement) = agentmodel.refinement() {
+        let state: &str = if agentmodel.is_refining() {"refining"} else {"idle"};
+        println!("refinement: from upsilon {}, {} probes per phase, {}", refinement.threshold, refinement.budget, state);
+      }
+      if let Some(restart) = agentmodel.restart_strategy() {
+        println!("restarts: {} with {:?}, epoch from step {}", agentmodel.restarts(), restart, agentmodel.xi_table().epoch_start());
+      }
+      println!("upsilon: {}", agentmodel.upsilon());
+      println!("evaluations: {}", agentmodel.xi_table().len());
+      println!("infeasible: {}", agentmodel.xi_table().infeasible().count());
+      println!("pending trials: {}", agentmodel.pending_trials().len());
+      if let Some(best) = agentmodel.xi_table().best() {
+        println!("best policy: {:?}", agentmodel.decode(&best.policy));
+        println!("best performance: {}", best.performance);
+        println!("best step: {}", best.step);
+      }
+      let pareto_front: Vec<&XiEntry> = agentmodel.pareto_front();
+      if !pareto_front.is_empty() {
+        println!("pareto front: {} entries", pareto_front.len());
+        for entry in pareto_front {
+          println!("  step {}: objectives {:?}, policy {:?}", entry.step, entry.objectives, agentmodel.decode(&entry.policy));
+        }
+      }
+      Ok(())
+    }
+    CliCommand::Compare { objective, dims, budget, seeds, target, output } => {
+      if budget == 0 || seeds == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "compare needs a positive --budget and --seeds"));
+      }
+      check_dims(dims)?;
+      let benchmark: Box<dyn Benchmark + Send + Sync> = objective.benchmark(dims)?;
+      let settings: CompareSettings = CompareSettings { budget, seeds, target, ..CompareSettings::default() };
+      let comparison: Comparison = compare(&*benchmark, &Contender::all(), &settings);
+      println!("{}: optimum {}, target {}", comparison.benchmark, comparison.optimum, comparison.target);
+      println!("{:<20} {:>12} {:>12} {:>12} {:>12} {:>8} {:>10}", "optimizer", "mean", "q25", "median", "q75", "success", "evals");
+      for report in &comparison.contenders {
+        let last: Vec<f64> = report.quantiles.iter().map(|x| {*x.1.last().unwrap()}).collect::<Vec<f64>>();
+        let evaluations: String = match report.mean_evaluations_to_target() {
+          Some(mean) => format!("{:.1}", mean),
+          None => String::from("-"),
+        };
+        println!(
+          "{:<20} {:>12.6} {:>12.6} {:>12.6} {:>12.6} {:>8.2} {:>10}",
+          report.name, report.mean.last().unwrap(), last[0], last[1], last[2], report.success_rate(), evaluations
+        );
+      }
+      if let Some(path) = output {
+        let mut out: File = File::create(path)?;
+        writeln!(out, "optimizer,evaluation,mean,q25,median,q75")?;
+        for report in &comparison.contenders {
+          for (i, mean) in report.mean.iter().enumerate() {
+            let quantiles: Vec<String> = report.quantiles
+            .iter()
+            .map(|x| {x.1[i].to_string()})
+            .collect::<Vec<String>>();
+            writeln!(out, "{},{},{},{}", report.name, i + 1, mean, quantiles.join(","))?;
+          }
+        }
+      }
+      Ok(())
+    }
+    CliCommand::Export { state, output } => {
+      let agentmodel: S3LAgent = S3LAgent::load(&state)?;
+      let mut out: Box<dyn Write> = match output {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout()),
+      };
+      let header: Vec<String> = (0..agentmodel.dims())
+      .map(|x| {format!("p{}", x)})
+      .collect::<Vec<String>>();
+      writeln!(out, "step,feasible,performance,{}", header.join(","))?;
+      for entry in agentmodel.xi_table() {
+        writeln!(out, "{}", csv_row(&agentmodel, entry))?;
+      }
+      Ok(())
+    }
   }
-  println!("==============DONE==============");
-  println!("The agent decided on policy {:?}. ", best_policy);
-  println!("This policy had performance {:?}. ", best_performance);
-  Done
+}
+
+fn csv_row(agentmodel: &S3LAgent, entry: &XiEntry) -> String {
+  let params: Vec<String> = agentmodel
+  .decode(&entry.policy)
+  .iter()
+  .map(|x| {x.to_string()})
+  .collect::<Vec<String>>();
+  let performance: String = if entry.feasible {entry.performance.to_string()} else {String::new()};
+  format!("{},{},{},{}", entry.step, entry.feasible, performance, params.join(","))
 }
@@ -0,0 +1,243 @@
+use rand::Rng;
+
+use crate::agent::S3LAgent;
+use crate::vector::{get_l2_n_dist, sub_vectors_into};
+
+/// A distance between normalized policies, used for avoidance and for moving toward the probabilistic max.
+pub trait Metric {
+  fn distance(&self, a: &[f64], b: &[f64]) -> f64;
+
+  /// The number of dimensions the metric is defined for, when it carries per-dimension settings. The agent refuses
+  /// a metric whose dimensions differ from its own.
+  fn dims(&self) -> Option<usize> {
+    None
+  }
+
+  /// A lower bound on the distance between two points whose coordinates on `axis` differ by `gap`, which lets the
+  /// spatial index prune. 0 is always correct but visits the whole table.
+  fn axis_lower_bound(&self, _axis: usize, _gap: f64) -> f64 {
+    0_f64
+  }
+
+  /// Writes the delta that moves `a` onto `b` into `out`. Defaults to `b - a`.
+  fn delta_into(&self, a: &[f64], b: &[f64], out: &mut [f64]) {
+    sub_vectors_into(a, b, out);
+  }
+
+  /// Brings a moved policy back into `[0, 1]^dims`. Defaults to clamping.
+  fn confine(&self, policy: &mut [f64]) {
+    for x in policy.iter_mut() {
+      *x = x.clamp(0_f64, 1_f64);
+    }
+  }
+}
+
+/// L2 distance, the default.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Euclidean;
+
+impl Metric for Euclidean {
+  fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+    get_l2_n_dist(a, b)
+  }
+
+  fn axis_lower_bound(&self, _axis: usize, gap: f64) -> f64 {
+    gap.abs()
+  }
+}
+
+/// L1 distance.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Manhattan;
+
+impl Metric for Manhattan {
+  fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+    .zip(b.iter())
+    .fold(0_f64, |a, x| {a + (x.0 - x.1).abs()})
+  }
+
+  fn axis_lower_bound(&self, _axis: usize, gap: f64) -> f64 {
+    gap.abs()
+  }
+}
+
+/// L∞ distance, the largest difference along any dimension.
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Chebyshev;
+
+impl Metric for Chebyshev {
+  fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+    .zip(b.iter())
+    .fold(0_f64, |a, x| {a.max((x.0 - x.1).abs())})
+  }
+
+  fn axis_lower_bound(&self, _axis: usize, gap: f64) -> f64 {
+    gap.abs()
+  }
+}
+
+/// L2 distance with a non-negative weight per dimension, for dimensions of unequal importance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WeightedEuclidean {
+  pub weights: Vec<f64>
+}
+
+impl Metric for WeightedEuclidean {
+  fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+    .zip(b.iter())
+    .zip(self.weights.iter())
+    .fold(0_f64, |a, x| {a + x.1 * (x.0.0 - x.0.1).powi(2)})
+    .sqrt()
+  }
+
+  fn dims(&self) -> Option<usize> {
+    Some(self.weights.len())
+  }
+
+  fn axis_lower_bound(&self, axis: usize, gap: f64) -> f64 {
+    self.weights[axis].sqrt() * gap.abs()
+  }
+}
+
+/// Distance under a covariance between dimensions, so correlated dimensions are not counted twice.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mahalanobis {
+  covariance: Vec<Vec<f64>>,
+  precision: Vec<Vec<f64>>
+}
+
+impl Mahalanobis {
+  /// Panics unless `covariance` is a square, invertible matrix.
+  pub fn new(covariance: Vec<Vec<f64>>) -> Mahalanobis {
+    let n: usize = covariance.len();
+    assert!(covariance.iter().all(|x| {x.len() == n}), "covariance must be square");
+    // Gauss-Jordan elimination with partial pivoting on [covariance | identity].
+    let mut work: Vec<Vec<f64>> = covariance.clone();
+    let mut precision: Vec<Vec<f64>> = (0..n)
+    .map(|i| {(0..n).map(|j| {if i == j {1_f64} else {0_f64}}).collect::<Vec<f64>>()})
+    .collect::<Vec<Vec<f64>>>();
+    for column in 0..n {
+      let pivot: usize = (column..n)
+      .max_by(|a, b| {work[*a][column].abs().partial_cmp(&work[*b][column].abs()).unwrap()})
+      .unwrap();
+      assert!(work[pivot][column].abs() > 1e-12, "covariance must be invertible");
+      work.swap(column, pivot);
+      precision.swap(column, pivot);
+      let scale: f64 = work[column][column];
+      for j in 0..n {
+        work[column][j] /= scale;
+        precision[column][j] /= scale;
+      }
+      for row in 0..n {
+        if row == column {
+          continue;
+        }
+        let factor: f64 = work[row][column];
+        for j in 0..n {
+          work[row][j] -= factor * work[column][j];
+          precision[row][j] -= factor * precision[column][j];
+        }
+      }
+    }
+    Mahalanobis {
+      covariance,
+      precision,
+    }
+  }
+
+  pub fn covariance(&self) -> &[Vec<f64>] {
+    &self.covariance
+  }
+}
+
+impl Metric for Mahalanobis {
+  fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+    let mut sum: f64 = 0_f64;
+    for (i, row) in self.precision.iter().enumerate() {
+      for (j, p) in row.iter().enumerate() {
+        sum += (a[i] - b[i]) * p * (a[j] - b[j]);
+      }
+    }
+    sum.max(0_f64).sqrt()
+  }
+
+  fn dims(&self) -> Option<usize> {
+    Some(self.covariance.len())
+  }
+
+  /// The smallest distance with that gap is reached when the other dimensions follow their regression on `axis`.
+  fn axis_lower_bound(&self, axis: usize, gap: f64) -> f64 {
+    gap.abs() / self.covariance[axis][axis].sqrt()
+  }
+}
+
+/// L2 distance where flagged dimensions wrap around, for angles and other cyclic parameters.
+///
+/// A periodic dimension's period is the whole normalized range, so 0 and 1 are the same point.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Periodic {
+  pub periodic: Vec<bool>
+}
+
+impl Periodic {
+  fn difference(&self, axis: usize, a: f64, b: f64) -> f64 {
+    if !self.periodic[axis] {
+      return b - a;
+    }
+    let wrapped: f64 = (b - a).rem_euclid(1_f64);
+    if wrapped > 0.5 {wrapped - 1_f64} else {wrapped}
+  }
+}
+
+impl Metric for Periodic {
+  fn distance(&self, a: &[f64], b: &[f64]) -> f64 {
+    a.iter()
+    .zip(b.iter())
+    .enumerate()
+    .fold(0_f64, |s, (i, x)| {s + self.difference(i, *x.0, *x.1).powi(2)})
+    .sqrt()
+  }
+
+  fn dims(&self) -> Option<usize> {
+    Some(self.periodic.len())
+  }
+
+  /// Periodic dimensions give no bound, since the far side of a split can wrap around to the near side.
+  fn axis_lower_bound(&self, axis: usize, gap: f64) -> f64 {
+    if self.periodic[axis] {0_f64} else {gap.abs()}
+  }
+
+  /// Takes the shorter way around on periodic dimensions.
+  fn delta_into(&self, a: &[f64], b: &[f64], out: &mut [f64]) {
+    for (i, o) in out.iter_mut().enumerate() {
+      *o = self.difference(i, a[i], b[i]);
+    }
+  }
+
+  fn confine(&self, policy: &mut [f64]) {
+    for (i, x) in policy.iter_mut().enumerate() {
+      *x = if self.periodic[i] {x.rem_euclid(1_f64)} else {x.clamp(0_f64, 1_f64)};
+    }
+  }
+}
+
+pub(crate) fn check_metric_dims(metric: &dyn Metric, dims: u32) {
+  if let Some(metric_dims) = metric.dims() {
+    assert_eq!(metric_dims, dims as usize, "the metric is defined for {} dimensions, not {}", metric_dims, dims);
+  }
+}
+
+impl<R: Rng> S3LAgent<R> {
+  pub fn metric(&self) -> &dyn Metric {
+    self.metric.as_ref()
+  }
+
+  /// Replaces the metric, returning the previous one. Panics if the metric is defined for other dimensions.
+  pub fn set_metric(&mut self, metric: Box<dyn Metric + Send>) -> Box<dyn Metric + Send> {
+    check_metric_dims(metric.as_ref(), self.dims);
+    std::mem::replace(&mut self.metric, metric)
+  }
+}
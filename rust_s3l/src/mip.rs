@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+use crate::vector::get_l2_n_dist;
+use crate::xi_table::{XiEntry, XiTable};
+
+/// Entries whose normalized policies are closer than this count as one order statistic for
+/// [`MipEstimator::ExtremeValue`].
+const MIN_ORDER_STATISTIC_SEPARATION: f64 = 0.05;
+
+/// Estimates the maximum ideal performance from the xi table when it is not known ahead of time.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MipEstimator {
+  /// Upper-endpoint estimate from the `k` best performances, `x(1) + (x(1) - x(k)) / (k - 1)`: the best value plus
+  /// the mean spacing between the top order statistics. `k = 2` is the Robson–Whitlock estimator.
+  ///
+  /// Entries next to a better one are skipped, so re-proposals around the best cannot shrink the spacing to zero.
+  /// While fewer than `k` entries are far enough apart, all of them are used.
+  ExtremeValue { k: usize },
+  /// The best performance improved by `kappa` standard deviations of all recorded performances.
+  OptimisticBound { kappa: f64 }
+}
+
+impl MipEstimator {
+  /// Returns `None` until the table holds enough entries. The estimate is never worse than the best performance.
+  pub fn estimate(&self, xi_table: &XiTable) -> Option<f64> {
+    let best: f64 = xi_table.best()?.performance;
+    let sign: f64 = xi_table.direction().sign();
+    let estimate: f64 = match self {
+      MipEstimator::ExtremeValue { k } => {
+        let k: usize = (*k).max(2);
+        if xi_table.feasible().count() < k {
+          return None;
+        }
+        let mut top: Vec<&XiEntry> = Vec::with_capacity(k);
+        for entry in xi_table.ranked() {
+          if top.iter().all(|x| {get_l2_n_dist(&x.policy, &entry.policy) >= MIN_ORDER_STATISTIC_SEPARATION}) {
+            top.push(entry);
+            if top.len() == k {
+              break;
+            }
+          }
+        }
+        if top.len() < 2 {
+          return None;
+        }
+        best + (best - top[top.len() - 1].performance) / ((top.len() - 1) as f64)
+      }
+      MipEstimator::OptimisticBound { kappa } => {
+        if xi_table.feasible().count() < 2 {
+          return None;
+        }
+        let n: f64 = xi_table.feasible().count() as f64;
+        let mean: f64 = xi_table.feasible().map(|x| {x.performance}).sum::<f64>() / n;
+        let variance: f64 = xi_table
+        .feasible()
+        .map(|x| {(x.performance - mean).powf(2_f64)})
+        .sum::<f64>() / (n - 1_f64);
+        best + sign * kappa * variance.sqrt()
+      }
+    };
+    Some(if xi_table.direction().is_better(best, estimate) {best} else {estimate})
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::agent::S3LAgent;
+  use crate::learning::learning_S3L;
+  use crate::mip::MipEstimator;
+  use crate::objective::TwoPeak;
+  use crate::stop::StopCriteria;
+
+  #[test]
+  fn extreme_value_estimate_keeps_improving() {
+    for seed in 0..8 {
+      let mut agentmodel: S3LAgent = S3LAgent::builder(2, 1.0)
+      .estimate_mip(MipEstimator::ExtremeValue { k: 10 })
+      .seed(seed)
+      .build();
+      learning_S3L(&mut agentmodel, &TwoPeak, &StopCriteria::new().max_evaluations(300));
+      let best: f64 = agentmodel.xi_table().best().unwrap().performance;
+      assert!(best > 2.99, "seed {} stalled at {}", seed, best);
+      assert!(agentmodel.mip() > best, "seed {} estimated {} at best {}", seed, agentmodel.mip(), best);
+    }
+  }
+}
@@ -0,0 +1,59 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::{Done, S3LAgent};
+use crate::xi_table::XiEntry;
+
+/// Settings for stochastic objectives.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NoiseHandling {
+  /// Standard errors each mean is penalized by when ranking, see [`XiTable::score`](crate::XiTable::score).
+  pub kappa: f64,
+  /// `learning_S3L` re-evaluates one promising policy after this many new ones.
+  pub reevaluate_every: usize,
+  /// Only the `top_k` best-scoring entries are re-evaluated.
+  pub top_k: usize,
+  /// No policy is evaluated more than this many times.
+  pub max_samples: u32
+}
+
+impl Default for NoiseHandling {
+  fn default() -> NoiseHandling {
+    NoiseHandling {
+      kappa: 1_f64,
+      reevaluate_every: 3,
+      top_k: 3,
+      max_samples: 10,
+    }
+  }
+}
+
+impl<R: Rng> S3LAgent<R> {
+  pub fn noise(&self) -> Option<NoiseHandling> {
+    self.noise
+  }
+
+  /// The step of the xi table entry most worth re-evaluating: the least-sampled of the `top_k` best, preferring the
+  /// better one on ties. `None` without noise handling or once they all reached `max_samples`.
+  pub fn select_reevaluation(&self) -> Option<u64> {
+    let noise: NoiseHandling = self.noise?;
+    self.xi_table
+    .top_k(noise.top_k)
+    .iter()
+    .filter(|x| {x.samples < noise.max_samples})
+    .min_by_key(|x| {x.samples})
+    .map(|x| {x.step})
+  }
+
+  /// Records another evaluation of the policy at `step` in the xi table. A NaN performance adds no sample.
+  pub fn add_sample(&mut self, step: u64, performance: f64) -> Done {
+    if performance.is_nan() {
+      return Done;
+    }
+    if let Some(entry) = self.xi_table.add_sample(step, performance) {
+      let entry: XiEntry = entry.clone();
+      self.observer.on_reevaluation(&entry, &self.space.decode(&entry.policy));
+    }
+    Done
+  }
+}
@@ -0,0 +1,117 @@
+use std::io::{self, Write};
+use std::process::{Child, ChildStdin, Command, Output, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use crate::vector::get_l2_n_dist;
+
+/// A black-box function scoring policies.
+pub trait Objective {
+  fn evaluate(&self, policy: &[f64]) -> f64;
+
+  /// Evaluates a policy that may turn out to be infeasible, returning `None` if so.
+  ///
+  /// By default a NaN performance marks the policy infeasible.
+  fn try_evaluate(&self, policy: &[f64]) -> Option<f64> {
+    let performance: f64 = self.evaluate(policy);
+    if performance.is_nan() {None} else {Some(performance)}
+  }
+}
+
+impl<F> Objective for F
+where
+  F: Fn(&[f64]) -> f64,
+{
+  fn evaluate(&self, policy: &[f64]) -> f64 {
+    self(policy)
+  }
+}
+
+/// What an agent is optimizing, saved with it so that a run can be resumed on the same objective.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ObjectiveSpec {
+  /// A built-in benchmark, by its [`Benchmark::name`](crate::Benchmark::name).
+  Benchmark(String),
+  Command(ExternalCommand)
+}
+
+/// Two-peak landscape with its global maximum at `[0.7, 0.7]` and a local maximum at `[0.2, 0.2]`.
+pub struct TwoPeak;
+
+impl Objective for TwoPeak {
+  fn evaluate(&self, policy: &[f64]) -> f64 {
+    let real_max: [f64; 2] = [0.7, 0.7];
+    let real_max_dist: f64 = get_l2_n_dist(policy, &real_max);
+    let initial: f64 = (2_f64 - real_max_dist) + 1_f64;
+    let local_max: [f64; 2] = [0.2, 0.2];
+    let local_max_dist: f64 = get_l2_n_dist(policy, &local_max);
+    let other_inital: f64 = 2_f64 - local_max_dist;
+    if real_max_dist < local_max_dist {
+      initial
+    } else {
+      other_inital
+    }
+  }
+}
+
+/// Runs an external program per evaluation.
+///
+/// The parameter values are written to its stdin as one space-separated line, and the first line of its stdout
+/// is parsed as the performance; printing `nan` marks the policy infeasible. An evaluation that cannot be run, exits
+/// with an error or prints no number is infeasible as well, see [`ExternalCommand::run`] for the reason.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ExternalCommand {
+  pub program: String,
+  pub args: Vec<String>
+}
+
+impl ExternalCommand {
+  pub fn new<S: Into<String>>(program: S, args: Vec<String>) -> ExternalCommand {
+    ExternalCommand {
+      program: program.into(),
+      args,
+    }
+  }
+
+  /// Runs the program once on `policy`, returning the performance it printed or why there is none.
+  pub fn run(&self, policy: &[f64]) -> io::Result<f64> {
+    let failure = |what: String| -> io::Error {io::Error::other(format!("{} {}", self.program, what))};
+    let mut child: Child = Command::new(&self.program)
+    .args(&self.args)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::piped())
+    .spawn()
+    .map_err(|e| {failure(format!("could not be run: {}", e))})?;
+    let line: String = policy
+    .iter()
+    .map(|x| {x.to_string()})
+    .collect::<Vec<String>>()
+    .join(" ");
+    let written: io::Result<()> = {
+      let mut stdin: ChildStdin = child.stdin.take().unwrap();
+      writeln!(stdin, "{}", line)
+    };
+    let output: Output = child
+    .wait_with_output()
+    .map_err(|e| {failure(format!("could not be waited for: {}", e))})?;
+    if !output.status.success() {
+      return Err(failure(format!("exited with {}", output.status)));
+    }
+    // A program may exit without reading its input; only its output matters then.
+    let stdout: String = String::from_utf8_lossy(&output.stdout).into_owned();
+    let first_line: &str = stdout.lines().next().unwrap_or("").trim();
+    first_line.parse::<f64>().map_err(|_e| {
+      match written {
+        Err(e) => failure(format!("could not be written to: {}", e)),
+        Ok(()) => failure(format!("printed {:?}, not a performance", first_line)),
+      }
+    })
+  }
+}
+
+impl Objective for ExternalCommand {
+  /// NaN, and so infeasible, when [`run`](ExternalCommand::run) fails.
+  fn evaluate(&self, policy: &[f64]) -> f64 {
+    self.run(policy).unwrap_or(f64::NAN)
+  }
+}
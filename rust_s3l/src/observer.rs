@@ -0,0 +1,192 @@
+use std::io::{self, Write};
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::learning::RunResult;
+use crate::xi_table::XiEntry;
+
+/// Receives the agent's progress events. Every callback defaults to doing nothing.
+///
+/// Policies are passed as parameter values, already mapped through the agent's search space.
+pub trait Observer {
+  /// The next point of the initial design, see [`Sampling`](crate::Sampling), was chosen for pure exploration.
+  fn on_exploration(&mut self, _params: &[f64]) {}
+
+  /// `select_policy` proposed a policy.
+  fn on_proposal(&mut self, _params: &[f64]) {}
+
+  /// An evaluated policy was recorded in the xi table.
+  fn on_evaluation(&mut self, _entry: &XiEntry, _params: &[f64]) {}
+
+  /// An existing entry was evaluated again; `entry` holds the updated mean and variance.
+  fn on_reevaluation(&mut self, _entry: &XiEntry, _params: &[f64]) {}
+
+  /// The entry just recorded beats every earlier one.
+  fn on_new_best(&mut self, _entry: &XiEntry, _params: &[f64]) {}
+
+  fn on_upsilon_change(&mut self, _previous: f64, _upsilon: f64) {}
+
+  /// The agent started its `restart`th new epoch, keeping `best` as the global best.
+  fn on_restart(&mut self, _restart: u32, _best: Option<&XiEntry>, _params: Option<&[f64]>) {}
+
+  /// Saving a [`Checkpoint`](crate::Checkpoint) to `path` failed; the run carries on.
+  fn on_checkpoint_failed(&mut self, _path: &Path, _error: &io::Error) {}
+
+  /// A learning run stopped.
+  fn on_stop(&mut self, _result: &RunResult) {}
+}
+
+/// Ignores every event.
+pub struct QuietObserver;
+
+impl Observer for QuietObserver {}
+
+/// Prints readable progress lines to stdout.
+pub struct HumanObserver;
+
+impl Observer for HumanObserver {
+  fn on_exploration(&mut self, params: &[f64]) {
+    println!("Agent explored policy {:?}. ", params);
+  }
+
+  fn on_proposal(&mut self, params: &[f64]) {
+    println!("Agent tried policy {:?}. ", params);
+  }
+
+  fn on_evaluation(&mut self, entry: &XiEntry, _params: &[f64]) {
+    if !entry.feasible {
+      println!("This policy is infeasible. ");
+    } else if !entry.objectives.is_empty() {
+      println!("This lead to objectives {:?}, scalarized to {:?}. ", entry.objectives, entry.performance);
+    } else {
+      println!("This lead to performance {:?}. ", entry.performance);
+    }
+  }
+
+  fn on_reevaluation(&mut self, entry: &XiEntry, params: &[f64]) {
+    println!("Agent re-evaluated policy {:?}, mean performance is now {:?} over {:?} samples. ", params, entry.performance, entry.samples);
+  }
+
+  fn on_new_best(&mut self, entry: &XiEntry, _params: &[f64]) {
+    println!("New best performance {:?} at step {:?}. ", entry.performance, entry.step);
+  }
+
+  fn on_upsilon_change(&mut self, _previous: f64, upsilon: f64) {
+    println!("Upsilon is now {:?}. ", upsilon);
+  }
+
+  fn on_restart(&mut self, restart: u32, best: Option<&XiEntry>, _params: Option<&[f64]>) {
+    match best {
+      Some(best) => println!("Agent restarted ({:?}), keeping best performance {:?}. ", restart, best.performance),
+      None => println!("Agent restarted ({:?}). ", restart),
+    }
+  }
+
+  fn on_checkpoint_failed(&mut self, path: &Path, error: &io::Error) {
+    println!("Could not save a checkpoint to {:?}: {}. ", path, error);
+  }
+
+  fn on_stop(&mut self, result: &RunResult) {
+    println!("==============DONE==============");
+    println!("Stopped because of {:?} after {:?} evaluations. ", result.stop_reason, result.evaluations);
+    if result.restarts > 0 {
+      println!("The agent restarted {:?} times. ", result.restarts);
+    }
+    match (&result.best, &result.best_params) {
+      (Some(best), Some(best_params)) => {
+        println!("The agent decided on policy {:?}. ", best_params);
+        println!("This policy had performance {:?}. ", best.performance);
+      }
+      _ => println!("The agent found no feasible policy. "),
+    }
+  }
+}
+
+/// Writes one JSON object per event, each tagged with an `"event"` field.
+pub struct JsonLinesObserver<W: Write> {
+  out: W
+}
+
+impl<W: Write> JsonLinesObserver<W> {
+  pub fn new(out: W) -> JsonLinesObserver<W> {
+    JsonLinesObserver {
+      out,
+    }
+  }
+
+  pub fn into_inner(self) -> W {
+    self.out
+  }
+
+  fn emit(&mut self, line: serde_json::Value) {
+    // Observers must not interrupt a run, so a failed write only loses the event.
+    let _ = writeln!(self.out, "{}", line);
+  }
+}
+
+impl<W: Write> Observer for JsonLinesObserver<W> {
+  fn on_exploration(&mut self, params: &[f64]) {
+    self.emit(json!({"event": "exploration", "params": params}));
+  }
+
+  fn on_proposal(&mut self, params: &[f64]) {
+    self.emit(json!({"event": "proposal", "params": params}));
+  }
+
+  fn on_evaluation(&mut self, entry: &XiEntry, params: &[f64]) {
+    self.emit(json!({
+      "event": "evaluation",
+      "step": entry.step,
+      "params": params,
+      "performance": if entry.feasible {Some(entry.performance)} else {None},
+      "objectives": entry.objectives,
+      "feasible": entry.feasible
+    }));
+  }
+
+  fn on_reevaluation(&mut self, entry: &XiEntry, params: &[f64]) {
+    self.emit(json!({
+      "event": "reevaluation",
+      "step": entry.step,
+      "params": params,
+      "performance": entry.performance,
+      "samples": entry.samples,
+      "variance": entry.variance
+    }));
+  }
+
+  fn on_new_best(&mut self, entry: &XiEntry, params: &[f64]) {
+    self.emit(json!({"event": "new_best", "step": entry.step, "params": params, "performance": entry.performance}));
+  }
+
+  fn on_upsilon_change(&mut self, previous: f64, upsilon: f64) {
+    self.emit(json!({"event": "upsilon_change", "previous": previous, "upsilon": upsilon}));
+  }
+
+  fn on_restart(&mut self, restart: u32, best: Option<&XiEntry>, params: Option<&[f64]>) {
+    self.emit(json!({
+      "event": "restart",
+      "restart": restart,
+      "best_params": params,
+      "best_performance": best.map(|x| {x.performance})
+    }));
+  }
+
+  fn on_checkpoint_failed(&mut self, path: &Path, error: &io::Error) {
+    self.emit(json!({"event": "checkpoint_failed", "path": path, "error": error.to_string()}));
+  }
+
+  fn on_stop(&mut self, result: &RunResult) {
+    self.emit(json!({
+      "event": "stop",
+      "reason": result.stop_reason,
+      "evaluations": result.evaluations,
+      "elapsed_secs": result.elapsed.as_secs_f64(),
+      "best_params": result.best_params,
+      "best_performance": result.best.as_ref().map(|x| {x.performance}),
+      "restarts": result.restarts,
+      "pareto_front": result.pareto_front.iter().map(|x| {&x.objectives}).collect::<Vec<&Vec<f64>>>()
+    }));
+  }
+}
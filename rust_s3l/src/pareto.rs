@@ -0,0 +1,154 @@
+use std::collections::BTreeMap;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::{Done, S3LAgent};
+use crate::learning::{run_learning, RunResult};
+use crate::performance::Direction;
+use crate::stop::StopCriteria;
+use crate::xi_table::XiEntry;
+
+/// A black-box function scoring policies on several objectives at once.
+///
+/// Every objective follows the agent's [`Direction`]; negate the ones that go the other way.
+pub trait MultiObjective {
+  fn evaluate(&self, policy: &[f64]) -> Vec<f64>;
+
+  /// Evaluates a policy that may turn out to be infeasible, returning `None` if so.
+  ///
+  /// By default a NaN in any objective marks the policy infeasible.
+  fn try_evaluate(&self, policy: &[f64]) -> Option<Vec<f64>> {
+    let objectives: Vec<f64> = self.evaluate(policy);
+    if objectives.iter().any(|x| {x.is_nan()}) {None} else {Some(objectives)}
+  }
+}
+
+impl<F> MultiObjective for F
+where
+  F: Fn(&[f64]) -> Vec<f64>,
+{
+  fn evaluate(&self, policy: &[f64]) -> Vec<f64> {
+    self(policy)
+  }
+}
+
+/// How an objective vector is reduced to the single performance that upsilon, mip and avoidance work on.
+///
+/// Missing weights default to 1, so an empty weight list weighs every objective equally.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Scalarization {
+  /// The weighted sum of the objectives.
+  WeightedSum(Vec<f64>),
+  /// The worst weighted objective, which unlike a sum also favours non-convex parts of the front.
+  Chebyshev(Vec<f64>)
+}
+
+impl Default for Scalarization {
+  fn default() -> Scalarization {
+    Scalarization::WeightedSum(Vec::new())
+  }
+}
+
+impl Scalarization {
+  pub fn scalarize(&self, objectives: &[f64], direction: Direction) -> f64 {
+    let weights: &[f64] = match self {
+      Scalarization::WeightedSum(weights) => weights,
+      Scalarization::Chebyshev(weights) => weights,
+    };
+    let weighted: Vec<f64> = objectives
+    .iter()
+    .enumerate()
+    .map(|(i, x)| {weights.get(i).copied().unwrap_or(1_f64) * x})
+    .collect::<Vec<f64>>();
+    match self {
+      Scalarization::WeightedSum(_) => weighted.iter().sum::<f64>(),
+      Scalarization::Chebyshev(_) => weighted
+      .iter()
+      .copied()
+      .reduce(|a, x| {if direction.is_better(a, x) {x} else {a}})
+      .unwrap_or(0_f64),
+    }
+  }
+}
+
+/// How `generate_probabilistic_max` orders the xi table of a multi-objective agent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ParetoRanking {
+  /// By non-dominated rank, then by crowding distance within a rank, so that sparse parts of the front attract.
+  #[default]
+  ParetoRank,
+  /// By scalarized score alone.
+  Scalarized
+}
+
+/// Settings for multi-objective agents.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct ParetoSettings {
+  pub scalarization: Scalarization,
+  pub ranking: ParetoRanking
+}
+
+impl<R: Rng> S3LAgent<R> {
+  pub fn pareto(&self) -> Option<&ParetoSettings> {
+    self.pareto.as_ref()
+  }
+
+  /// The non-dominated entries of the xi table, in the order they were recorded.
+  pub fn pareto_front(&self) -> Vec<&XiEntry> {
+    self.xi_table.pareto_front()
+  }
+
+  /// Records the objective vector of an evaluated normalized policy, scalarized into its performance. A NaN in any
+  /// objective records it as infeasible.
+  pub fn update_xi_table_objectives(&mut self, policy: Vec<f64>, objectives: Vec<f64>) -> Done {
+    if objectives.iter().any(|x| {x.is_nan()}) {
+      return self.update_xi_table_infeasible(policy);
+    }
+    let performance: f64 = self.pareto
+    .as_ref()
+    .map(|x| {x.scalarization.scalarize(&objectives, self.direction())})
+    .unwrap_or_else(|| {Scalarization::default().scalarize(&objectives, self.direction())});
+    let previous_best: (Option<f64>, Option<f64>) = (self.best_score(), self.epoch_best_score());
+    self.xi_table.push_objectives(policy, objectives, performance, BTreeMap::new());
+    self.report_evaluation(previous_best)
+  }
+}
+
+/// Evaluates a policy and records its objectives, or records it as infeasible.
+fn evaluate_objectives_and_record<R: Rng, M: MultiObjective + ?Sized>(agentmodel: &mut S3LAgent<R>, objective: &M, policy: Vec<f64>, params: &[f64]) -> Done {
+  let objectives: Option<Vec<f64>> = if agentmodel.is_feasible(&policy) {objective.try_evaluate(params)} else {None};
+  match objectives {
+    Some(objectives) => agentmodel.update_xi_table_objectives(policy, objectives),
+    None => agentmodel.update_xi_table_infeasible(policy),
+  }
+}
+
+/// Selects a policy, evaluates every objective and records the result.
+pub fn pareto_learn_step<R: Rng, M: MultiObjective + ?Sized>(agentmodel: &mut S3LAgent<R>, objective: &M) -> XiEntry {
+  let policy: Vec<f64> = agentmodel.select_policy();
+  let params: Vec<f64> = agentmodel.decode(&policy);
+  evaluate_objectives_and_record(agentmodel, objective, policy, &params);
+  agentmodel.xi_table().last().unwrap().clone()
+}
+
+/// Evaluates the next point of the initial design on every objective and records the result.
+pub fn pareto_exploration_step<R: Rng, M: MultiObjective + ?Sized>(agentmodel: &mut S3LAgent<R>, objective: &M) -> Done {
+  let policy: Vec<f64> = agentmodel.design_policy();
+  let params: Vec<f64> = agentmodel.decode(&policy);
+  agentmodel.observer.on_exploration(&params);
+  evaluate_objectives_and_record(agentmodel, objective, policy, &params)
+}
+
+/// [`learning_S3L`](crate::learning_S3L) for a multi-objective function. Stop criteria apply to the scalarized
+/// performance, and noisy re-evaluation is not supported.
+#[allow(non_snake_case)]
+pub fn learning_S3L_pareto<R: Rng, M: MultiObjective + ?Sized>(agentmodel: &mut S3LAgent<R>, objective: &M, stop: &StopCriteria) -> RunResult {
+  run_learning(
+    agentmodel,
+    stop,
+    |x| {pareto_exploration_step(x, objective);},
+    |x| {pareto_learn_step(x, objective);},
+    |_x| {false},
+  )
+}
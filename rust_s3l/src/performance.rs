@@ -0,0 +1,75 @@
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+/// Whether higher or lower performance is better.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Direction {
+  #[default]
+  Maximize,
+  Minimize
+}
+
+impl Direction {
+  /// `1.0` when maximizing and `-1.0` when minimizing, so `sign * performance` is always higher-is-better.
+  pub fn sign(self) -> f64 {
+    match self {
+      Direction::Maximize => 1_f64,
+      Direction::Minimize => -1_f64,
+    }
+  }
+
+  /// Orders `a` against `b` so that `Greater` means `a` is better. NaN is ordered as by [`f64::total_cmp`] instead
+  /// of panicking.
+  pub fn compare(self, a: f64, b: f64) -> Ordering {
+    let (a, b): (f64, f64) = (self.sign() * a, self.sign() * b);
+    a.partial_cmp(&b).unwrap_or_else(|| {a.total_cmp(&b)})
+  }
+
+  pub fn is_better(self, a: f64, b: f64) -> bool {
+    self.compare(a, b) == Ordering::Greater
+  }
+
+  /// Whether objective vector `a` is at least as good as `b` everywhere and better somewhere.
+  pub fn dominates(self, a: &[f64], b: &[f64]) -> bool {
+    let no_worse: bool = a.iter().zip(b.iter()).all(|x| {!self.is_better(*x.1, *x.0)});
+    let better: bool = a.iter().zip(b.iter()).any(|x| {self.is_better(*x.0, *x.1)});
+    no_worse && better
+  }
+}
+
+/// How a performance is turned into a utility in `[0, 1]`, where 1 means the ideal performance `mip` was reached.
+///
+/// Upsilon and the split between avoided minima and attracting maxima are computed on this utility.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Normalization {
+  /// `performance / mip` when maximizing and `mip / performance` when minimizing. Only meaningful when `mip` and
+  /// every performance are positive; negative values invert the ranking.
+  #[default]
+  Ratio,
+  /// Position between the worst performance seen so far and `mip`. Works for any real-valued objective, and is the
+  /// default when minimizing or when `mip` is not positive.
+  MinMax
+}
+
+impl Normalization {
+  /// The normalization used when none is given: [`Ratio`](Normalization::Ratio) when maximizing toward a positive
+  /// `mip`, [`MinMax`](Normalization::MinMax) otherwise.
+  pub fn default_for(direction: Direction, mip: f64) -> Normalization {
+    if direction == Direction::Maximize && mip > 0_f64 {Normalization::Ratio} else {Normalization::MinMax}
+  }
+
+  pub fn utility(self, performance: f64, mip: f64, worst: f64, direction: Direction) -> f64 {
+    match (self, direction) {
+      (Normalization::Ratio, Direction::Maximize) => performance / mip,
+      (Normalization::Ratio, Direction::Minimize) => mip / performance,
+      (Normalization::MinMax, _) => {
+        let range: f64 = mip - worst;
+        if range == 0_f64 {
+          return if performance == mip {1_f64} else {0_f64};
+        }
+        (performance - worst) / range
+      }
+    }
+  }
+}
@@ -0,0 +1,362 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::agent::{S3LAgent, Scratch};
+use crate::metric::Euclidean;
+use crate::mip::MipEstimator;
+use crate::noise::NoiseHandling;
+use crate::objective::ObjectiveSpec;
+use crate::observer::QuietObserver;
+use crate::pareto::ParetoSettings;
+use crate::performance::Normalization;
+use crate::refine::{RefineState, Refinement};
+use crate::restart::Restart;
+use crate::schedule::Schedule;
+use crate::space::SearchSpace;
+use crate::warm_up::WarmUp;
+use crate::xi_table::XiTable;
+
+/// Version of the saved agent layout, bumped whenever a field is added to the saved state.
+///
+/// JSON files of any earlier version still load, with the missing fields defaulted. Binary files are positional, so
+/// they only load at exactly this version.
+pub const FORMAT_VERSION: u32 = 2;
+
+const FORMAT_NAME: &str = "rust_s3l";
+const BINARY_MAGIC: &[u8; 4] = b"S3L\0";
+
+/// On-disk encoding for [`S3LAgent::save`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+  /// Human-readable, pretty-printed JSON.
+  Json,
+  /// `S3L\0`, a little-endian `u32` version, then a bincode payload.
+  Binary
+}
+
+impl Format {
+  /// The format of the agent file at `path`, told apart by the binary magic bytes.
+  pub fn of_file<P: AsRef<Path>>(path: P) -> io::Result<Format> {
+    let mut magic: [u8; 4] = [0; 4];
+    let read: usize = io::Read::read(&mut fs::File::open(path)?, &mut magic)?;
+    Ok(if read == magic.len() && &magic == BINARY_MAGIC {Format::Binary} else {Format::Json})
+  }
+}
+
+/// Saves the agent every `every` evaluations of a learning run, so that a crash loses little work.
+///
+/// Failed saves are reported to the observer and do not stop the run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Checkpoint {
+  pub path: PathBuf,
+  pub format: Format,
+  pub every: usize
+}
+
+#[derive(Serialize, Deserialize)]
+struct AgentState {
+  dims: u32,
+  mip: f64,
+  #[serde(default)]
+  mip_estimator: Option<MipEstimator>,
+  #[serde(default)]
+  normalization: Normalization,
+  #[serde(default)]
+  noise: Option<NoiseHandling>,
+  #[serde(default)]
+  pareto: Option<ParetoSettings>,
+  j: u32,
+  #[serde(default)]
+  schedule: Schedule,
+  #[serde(default)]
+  exponent: Option<f64>,
+  #[serde(default)]
+  window_trials: usize,
+  #[serde(default)]
+  window_successes: usize,
+  #[serde(default)]
+  refinement: Option<Refinement>,
+  #[serde(default)]
+  refine_state: RefineState,
+  #[serde(default)]
+  restart: Option<Restart>,
+  #[serde(default)]
+  restarts: u32,
+  #[serde(default)]
+  stagnation: usize,
+  #[serde(default)]
+  objective: Option<ObjectiveSpec>,
+  xi_table: XiTable,
+  upsilon: f64,
+  space: SearchSpace,
+  pending: BTreeMap<u64, Vec<f64>>,
+  next_trial_id: u64,
+  #[serde(default)]
+  warm_up: WarmUp,
+  #[serde(default)]
+  design_drawn: u64,
+  #[serde(default)]
+  design_start: u64,
+  #[serde(default)]
+  latin_hypercube: Vec<Vec<f64>>
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonFile {
+  format: String,
+  version: u32,
+  agent: AgentState
+}
+
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> io::Error {
+  io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+fn check_version(version: u32, format: Format) -> io::Result<()> {
+  let supported: bool = match format {
+    Format::Json => (1..=FORMAT_VERSION).contains(&version),
+    Format::Binary => version == FORMAT_VERSION,
+  };
+  if !supported {
+    return Err(invalid_data(format!("unsupported {:?} agent format version {}", format, version)));
+  }
+  Ok(())
+}
+
+impl<R: Rng> S3LAgent<R> {
+  /// Writes the agent's state to `path`.
+  ///
+  /// The random number generator and observer are not saved; a loaded agent continues from a fresh stream and
+  /// reports to a [`QuietObserver`](crate::QuietObserver) until [`S3LAgent::set_observer`] is called. Constraints
+  /// and the metric are not saved either: add them back with [`S3LAgent::add_constraint`] and
+  /// [`S3LAgent::set_metric`], and neither is the [`Checkpoint`].
+  ///
+  /// The state is written to a temporary file next to `path` first, so an interrupted save leaves the previous file
+  /// intact.
+  pub fn save<P: AsRef<Path>>(&self, path: P, format: Format) -> io::Result<()> {
+    let state: AgentState = AgentState {
+      dims: self.dims,
+      mip: self.mip,
+      mip_estimator: self.mip_estimator,
+      normalization: self.normalization,
+      noise: self.noise,
+      pareto: self.pareto.clone(),
+      j: self.j,
+      schedule: self.schedule,
+      exponent: Some(self.exponent),
+      window_trials: self.window_trials,
+      window_successes: self.window_successes,
+      refinement: self.refinement,
+      refine_state: self.refine_state.clone(),
+      restart: self.restart,
+      restarts: self.restarts,
+      stagnation: self.stagnation,
+      objective: self.objective.clone(),
+      xi_table: self.xi_table.clone(),
+      upsilon: self.upsilon,
+      space: self.space.clone(),
+      pending: self.pending.clone(),
+      next_trial_id: self.next_trial_id,
+      warm_up: self.warm_up,
+      design_drawn: self.design_drawn,
+      design_start: self.design_start,
+      latin_hypercube: self.latin_hypercube.clone(),
+    };
+    let bytes: Vec<u8> = match format {
+      Format::Json => {
+        let file: JsonFile = JsonFile {
+          format: FORMAT_NAME.to_string(),
+          version: FORMAT_VERSION,
+          agent: state,
+        };
+        serde_json::to_vec_pretty(&file)?
+      }
+      Format::Binary => {
+        let mut bytes: Vec<u8> = BINARY_MAGIC.to_vec();
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, &state).map_err(invalid_data)?;
+        bytes
+      }
+    };
+    let path: &Path = path.as_ref();
+    let mut temporary: PathBuf = path.to_path_buf();
+    temporary.as_mut_os_string().push(".tmp");
+    fs::write(&temporary, bytes)?;
+    fs::rename(&temporary, path)
+  }
+
+  pub fn checkpoint(&self) -> Option<&Checkpoint> {
+    self.checkpoint.as_ref()
+  }
+
+  /// Replaces the checkpoint settings, returning the previous ones.
+  pub fn set_checkpoint(&mut self, checkpoint: Option<Checkpoint>) -> Option<Checkpoint> {
+    std::mem::replace(&mut self.checkpoint, checkpoint)
+  }
+
+  /// Saves the agent if a checkpoint is due after `evaluations` evaluations of the current run.
+  pub(crate) fn save_checkpoint(&mut self, evaluations: usize) {
+    let checkpoint: Checkpoint = match &self.checkpoint {
+      Some(checkpoint) if checkpoint.every > 0 && evaluations.is_multiple_of(checkpoint.every) => checkpoint.clone(),
+      _ => return,
+    };
+    if let Err(e) = self.save(&checkpoint.path, checkpoint.format) {
+      self.observer.on_checkpoint_failed(&checkpoint.path, &e);
+    }
+  }
+}
+
+impl S3LAgent {
+  /// Reads an agent written by [`S3LAgent::save`] in either format, seeding a new stream from system entropy.
+  pub fn load<P: AsRef<Path>>(path: P) -> io::Result<S3LAgent> {
+    S3LAgent::load_with_rng(path, StdRng::from_entropy())
+  }
+
+  /// Reads an agent written by [`S3LAgent::save`] in either format, drawing from `rng` from now on.
+  pub fn load_with_rng<P: AsRef<Path>, R: Rng>(path: P, rng: R) -> io::Result<S3LAgent<R>> {
+    let bytes: Vec<u8> = fs::read(path)?;
+    let state: AgentState = if bytes.starts_with(BINARY_MAGIC) {
+      if bytes.len() < 8 {
+        return Err(invalid_data("truncated agent header"));
+      }
+      let mut version_bytes: [u8; 4] = [0; 4];
+      version_bytes.copy_from_slice(&bytes[4..8]);
+      check_version(u32::from_le_bytes(version_bytes), Format::Binary)?;
+      bincode::deserialize::<AgentState>(&bytes[8..]).map_err(invalid_data)?
+    } else {
+      let value: serde_json::Value = serde_json::from_slice::<serde_json::Value>(&bytes)?;
+      let has_normalization: bool = value["agent"].get("normalization").is_some();
+      let file: JsonFile = serde_json::from_value::<JsonFile>(value)?;
+      if file.format != FORMAT_NAME {
+        return Err(invalid_data(format!("not a {} agent file", FORMAT_NAME)));
+      }
+      check_version(file.version, Format::Json)?;
+      let mut state: AgentState = file.agent;
+      // Files written before the normalization was saved get the one the builder would have picked.
+      if !has_normalization {
+        state.normalization = Normalization::default_for(state.xi_table.direction(), state.mip);
+      }
+      state
+    };
+    if state.space.dims() != state.dims {
+      return Err(invalid_data("search space does not match dims"));
+    }
+    Ok(S3LAgent {
+      dims: state.dims,
+      mip: state.mip,
+      mip_estimator: state.mip_estimator,
+      normalization: state.normalization,
+      noise: state.noise,
+      pareto: state.pareto,
+      j: state.j,
+      schedule: state.schedule,
+      exponent: state.exponent.unwrap_or(state.j as f64),
+      window_trials: state.window_trials,
+      window_successes: state.window_successes,
+      refinement: state.refinement,
+      refine_state: state.refine_state,
+      restart: state.restart,
+      restarts: state.restarts,
+      stagnation: state.stagnation,
+      xi_table: state.xi_table,
+      upsilon: state.upsilon,
+      rng,
+      space: state.space,
+      pending: state.pending,
+      next_trial_id: state.next_trial_id,
+      observer: Box::new(QuietObserver),
+      constraints: Vec::new(),
+      metric: Box::new(Euclidean),
+      checkpoint: None,
+      objective: state.objective,
+      warm_up: state.warm_up,
+      design_drawn: state.design_drawn,
+      design_start: state.design_start,
+      latin_hypercube: state.latin_hypercube,
+      scratch: Scratch::default(),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::fs;
+  use std::path::PathBuf;
+
+  use crate::agent::S3LAgent;
+  use crate::learning::learning_S3L;
+  use crate::noise::NoiseHandling;
+  use crate::objective::TwoPeak;
+  use crate::performance::Normalization;
+  use crate::persist::{Format, FORMAT_VERSION};
+  use crate::restart::Restart;
+  use crate::stop::StopCriteria;
+  use crate::trial::Trial;
+
+  fn round_trip(format: Format) {
+    let path: PathBuf = std::env::temp_dir().join(format!("rust_s3l_round_trip_{}_{:?}", std::process::id(), format));
+    let mut agentmodel: S3LAgent = S3LAgent::builder(2, 3.1)
+    .noise(NoiseHandling::default())
+    .restart(Restart::Stagnation { patience: 10 })
+    .seed(1)
+    .build();
+    learning_S3L(&mut agentmodel, &TwoPeak, &StopCriteria::new().max_evaluations(60));
+    let trial: Trial = agentmodel.ask();
+    agentmodel.save(&path, format).unwrap();
+    assert_eq!(Format::of_file(&path).unwrap(), format);
+    let loaded: S3LAgent = S3LAgent::load(&path).unwrap();
+    let mut resaved: PathBuf = path.clone();
+    resaved.set_extension("resaved");
+    loaded.save(&resaved, format).unwrap();
+    assert_eq!(loaded.xi_table(), agentmodel.xi_table());
+    assert_eq!(loaded.pending_trials(), vec![trial]);
+    assert_eq!(loaded.upsilon(), agentmodel.upsilon());
+    assert_eq!(loaded.restarts(), agentmodel.restarts());
+    assert_eq!(fs::read(&resaved).unwrap(), fs::read(&path).unwrap());
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(&resaved).unwrap();
+  }
+
+  #[test]
+  fn json_round_trip() {
+    round_trip(Format::Json);
+  }
+
+  #[test]
+  fn binary_round_trip() {
+    round_trip(Format::Binary);
+  }
+
+  #[test]
+  fn binary_of_another_version_is_rejected() {
+    let path: PathBuf = std::env::temp_dir().join(format!("rust_s3l_old_binary_{}", std::process::id()));
+    S3LAgent::builder(2, 3.1).seed(1).build().save(&path, Format::Binary).unwrap();
+    let mut bytes: Vec<u8> = fs::read(&path).unwrap();
+    bytes[4..8].copy_from_slice(&(FORMAT_VERSION - 1).to_le_bytes());
+    fs::write(&path, bytes).unwrap();
+    let error: String = S3LAgent::load(&path).err().unwrap().to_string();
+    fs::remove_file(&path).unwrap();
+    assert!(error.contains("version"), "{}", error);
+  }
+
+  #[test]
+  fn version_1_json_derives_its_normalization_from_the_mip() {
+    let original: &str = include_str!("../testdata/agent_v1.json");
+    let path: PathBuf = std::env::temp_dir().join(format!("rust_s3l_v1_json_{}", std::process::id()));
+    fs::write(&path, original).unwrap();
+    let loaded: S3LAgent = S3LAgent::load(&path).unwrap();
+    assert_eq!(loaded.mip(), -1_f64);
+    assert_eq!(loaded.normalization(), Normalization::MinMax);
+    assert_eq!(loaded.xi_table().len(), 2);
+    fs::write(&path, original.replace("\"mip\": -1.0", "\"mip\": 3.0")).unwrap();
+    let loaded: S3LAgent = S3LAgent::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.normalization(), Normalization::Ratio);
+  }
+}
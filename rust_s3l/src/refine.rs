@@ -0,0 +1,97 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::S3LAgent;
+use crate::xi_table::XiEntry;
+
+/// Settings for a compass search around the incumbent once the agent is confident it is near the optimum.
+///
+/// While a refinement phase is active, every proposal probes the best policy one step up or down a single axis. A
+/// new best moves the probes to it; a full sweep without one halves the step. The phase ends when the step drops below
+/// `min_step` or after `budget` probes, and the next phase starts once S3L finds a new best.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Refinement {
+  /// Upsilon from which refinement starts.
+  pub threshold: f64,
+  /// The first step along each axis, in the normalized range.
+  pub initial_step: f64,
+  pub min_step: f64,
+  /// The most probes in one phase.
+  pub budget: usize
+}
+
+impl Default for Refinement {
+  fn default() -> Refinement {
+    Refinement {
+      threshold: 0.8,
+      initial_step: 0.05,
+      min_step: 1e-3,
+      budget: 50,
+    }
+  }
+}
+
+/// Progress of the current refinement phase.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub(crate) struct RefineState {
+  active: bool,
+  /// Step of the incumbent being probed.
+  center: Option<u64>,
+  step: f64,
+  /// Index into the `2 * dims` probes of a sweep: up then down along each axis in turn.
+  next_probe: usize,
+  probes: usize
+}
+
+impl<R: Rng> S3LAgent<R> {
+  pub fn refinement(&self) -> Option<Refinement> {
+    self.refinement
+  }
+
+  /// Whether proposals are currently probing around the incumbent.
+  pub fn is_refining(&self) -> bool {
+    self.refine_state.active
+  }
+
+  /// The next probe around the incumbent, or `None` when no refinement phase is active.
+  pub(crate) fn refinement_probe(&mut self) -> Option<Vec<f64>> {
+    let refinement: Refinement = self.refinement?;
+    if self.upsilon < refinement.threshold {
+      return None;
+    }
+    let best: &XiEntry = self.xi_table.epoch_best()?;
+    let state: &mut RefineState = &mut self.refine_state;
+    if state.center != Some(best.step) {
+      state.center = Some(best.step);
+      state.next_probe = 0;
+      if !state.active {
+        state.active = true;
+        state.step = refinement.initial_step;
+        state.probes = 0;
+      }
+    }
+    while state.active {
+      if state.probes >= refinement.budget || state.step < refinement.min_step {
+        state.active = false;
+        break;
+      }
+      if state.next_probe >= 2 * best.policy.len() {
+        state.step /= 2_f64;
+        state.next_probe = 0;
+        continue;
+      }
+      let axis: usize = state.next_probe / 2;
+      let sign: f64 = if state.next_probe.is_multiple_of(2) {1_f64} else {-1_f64};
+      state.next_probe += 1;
+      let mut policy: Vec<f64> = best.policy.clone();
+      policy[axis] += sign * state.step;
+      self.metric.confine(&mut policy);
+      // A probe pushed back onto the incumbent by the bounds would be a wasted evaluation.
+      if policy != best.policy {
+        state.probes += 1;
+        return Some(policy);
+      }
+    }
+    None
+  }
+}
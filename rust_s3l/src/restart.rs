@@ -0,0 +1,69 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::{Done, S3LAgent};
+use crate::refine::RefineState;
+
+/// When the agent gives up on the region it is exploiting and starts a new epoch.
+///
+/// A restart keeps every entry in the xi table and the global best, but only entries from the new epoch steer the
+/// agent, so it begins again with a fresh initial design and zero upsilon.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Restart {
+  /// Restarts after `patience` evaluations past the warm-up without a new best in the current epoch.
+  Stagnation { patience: usize },
+  /// Restarts like [`Restart::Stagnation`], and every restart multiplies the warm-up size and the patience by
+  /// `growth`, so each epoch explores more widely before it exploits, as in IPOP-CMA-ES.
+  Ipop { patience: usize, growth: f64 }
+}
+
+impl<R: Rng> S3LAgent<R> {
+  pub fn restart_strategy(&self) -> Option<Restart> {
+    self.restart
+  }
+
+  /// How many times the agent has restarted.
+  pub fn restarts(&self) -> u32 {
+    self.restarts
+  }
+
+  /// How much the warm-up and patience have grown through IPOP restarts, 1 otherwise.
+  pub(crate) fn exploration_growth(&self) -> f64 {
+    match self.restart {
+      Some(Restart::Ipop { growth, .. }) => growth.powi(self.restarts as i32),
+      _ => 1_f64,
+    }
+  }
+
+  /// Starts a new epoch now, whatever the restart strategy, and reports it to the observer.
+  pub fn restart(&mut self) -> Done {
+    self.restarts += 1;
+    self.xi_table.start_epoch();
+    self.stagnation = 0;
+    self.design_start = self.design_drawn;
+    self.refine_state = RefineState::default();
+    self.exponent = self.schedule.initial_exponent(self.j);
+    self.window_trials = 0;
+    self.window_successes = 0;
+    let best_params: Option<Vec<f64>> = self.xi_table.best().map(|x| {self.space.decode(&x.policy)});
+    self.observer.on_restart(self.restarts, self.xi_table.best(), best_params.as_deref());
+    Done
+  }
+
+  /// Counts an evaluation toward stagnation, `improved` when it found a new best of the current epoch, and
+  /// restarts once the patience runs out.
+  pub(crate) fn track_stagnation(&mut self, improved: bool) -> Done {
+    let patience: usize = match self.restart {
+      Some(Restart::Stagnation { patience }) | Some(Restart::Ipop { patience, .. }) => patience,
+      None => return Done,
+    };
+    if self.xi_table.epoch().len() <= self.warm_up_size() {
+      return Done;
+    }
+    self.stagnation = if improved {0} else {self.stagnation + 1};
+    if self.stagnation as f64 >= (patience as f64 * self.exploration_growth()).round() {
+      return self.restart();
+    }
+    Done
+  }
+}
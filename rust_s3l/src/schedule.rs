@@ -0,0 +1,85 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::{Done, S3LAgent};
+
+/// How the exponent behind the upsilon boost and the step scaling changes over a run.
+///
+/// A higher exponent keeps upsilon and the steps toward the probabilistic max small, so the agent explores more; a
+/// lower one exploits the best policies harder.
+#[derive(Clone, Copy, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub enum Schedule {
+  /// The builder's `j` throughout, as in the original algorithm.
+  #[default]
+  Constant,
+  /// Moves linearly from `start` to `end` over the first `steps` evaluations after the warm-up, then holds.
+  Annealed { start: f64, end: f64, steps: u64 },
+  /// A 1/5th success rule: after every `window` evaluations past the warm-up, the exponent is multiplied by `factor`
+  /// if more than a fifth of them found a new best and divided by it if fewer did, within `[min, max]`.
+  SuccessRule { window: usize, factor: f64, min: f64, max: f64 }
+}
+
+impl Schedule {
+  /// A success rule over windows of 10 evaluations, scaling by 1.5 within `[0.5, 8]`.
+  pub fn success_rule() -> Schedule {
+    Schedule::SuccessRule {
+      window: 10,
+      factor: 1.5,
+      min: 0.5,
+      max: 8_f64,
+    }
+  }
+
+  /// The exponent a run starts with, given the builder's `j`.
+  pub fn initial_exponent(&self, j: u32) -> f64 {
+    match *self {
+      Schedule::Constant => j as f64,
+      Schedule::Annealed { start, .. } => start,
+      Schedule::SuccessRule { min, max, .. } => (j as f64).clamp(min, max),
+    }
+  }
+}
+
+impl<R: Rng> S3LAgent<R> {
+  pub fn schedule(&self) -> Schedule {
+    self.schedule
+  }
+
+  /// The exponent currently used in place of `j`.
+  pub fn exponent(&self) -> f64 {
+    self.exponent
+  }
+
+  /// Advances the schedule after an evaluation, `improved` when it found a new best of the current epoch.
+  pub(crate) fn advance_schedule(&mut self, improved: bool) -> Done {
+    let evaluated: u64 = self.xi_table.epoch().len().saturating_sub(self.warm_up_size()) as u64;
+    if evaluated == 0 {
+      return Done;
+    }
+    match self.schedule {
+      Schedule::Constant => {}
+      Schedule::Annealed { start, end, steps } => {
+        let progress: f64 = if steps == 0 {1_f64} else {(evaluated as f64 / steps as f64).min(1_f64)};
+        self.exponent = start + progress * (end - start);
+      }
+      Schedule::SuccessRule { window, factor, min, max } => {
+        self.window_trials += 1;
+        if improved {
+          self.window_successes += 1;
+        }
+        if self.window_trials >= window {
+          let rate: f64 = self.window_successes as f64 / self.window_trials as f64;
+          if rate > 0.2 {
+            self.exponent *= factor;
+          } else if rate < 0.2 {
+            self.exponent /= factor;
+          }
+          self.exponent = self.exponent.clamp(min, max);
+          self.window_trials = 0;
+          self.window_successes = 0;
+        }
+      }
+    }
+    Done
+  }
+}
@@ -0,0 +1,122 @@
+use serde::{Deserialize, Serialize};
+
+/// The kind and range of a single search dimension.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Param {
+  Continuous { low: f64, high: f64 },
+  /// Continuous, but sampled uniformly in `ln` space. Both bounds must be positive.
+  LogScale { low: f64, high: f64 },
+  Integer { low: i64, high: i64 },
+  /// Decodes to the index of the chosen entry.
+  Categorical(Vec<String>)
+}
+
+impl Param {
+  fn decode(&self, u: f64) -> f64 {
+    let u: f64 = u.clamp(0_f64, 1_f64);
+    match self {
+      Param::Continuous { low, high } => low + u * (high - low),
+      Param::LogScale { low, high } => (low.ln() + u * (high.ln() - low.ln())).exp(),
+      Param::Integer { low, high } => {
+        let n: f64 = (high - low + 1) as f64;
+        ((*low as f64) + (u * n).floor()).min(*high as f64)
+      }
+      Param::Categorical(choices) => {
+        let n: f64 = choices.len() as f64;
+        (u * n).floor().min(n - 1_f64)
+      }
+    }
+  }
+
+  fn encode(&self, x: f64) -> f64 {
+    let u: f64 = match self {
+      Param::Continuous { low, high } => (x - low) / (high - low),
+      Param::LogScale { low, high } => (x.ln() - low.ln()) / (high.ln() - low.ln()),
+      Param::Integer { low, high } => (x - (*low as f64) + 0.5) / ((high - low + 1) as f64),
+      Param::Categorical(choices) => (x + 0.5) / (choices.len() as f64),
+    };
+    u.clamp(0_f64, 1_f64)
+  }
+}
+
+/// A bounded search space. The agent works in `[0, 1]^dims` and maps back through it.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+pub struct SearchSpace {
+  params: Vec<Param>
+}
+
+impl SearchSpace {
+  pub fn new() -> SearchSpace {
+    SearchSpace {
+      params: Vec::new(),
+    }
+  }
+
+  /// The implicit `[0, 1]^dims` space.
+  pub fn unit(dims: u32) -> SearchSpace {
+    (0..dims).fold(SearchSpace::new(), |a, _x| {a.continuous(0_f64, 1_f64)})
+  }
+
+  pub fn param(mut self, param: Param) -> SearchSpace {
+    match &param {
+      Param::Continuous { low, high } => assert!(low < high),
+      Param::LogScale { low, high } => assert!(0_f64 < *low && low < high),
+      Param::Integer { low, high } => assert!(low <= high),
+      Param::Categorical(choices) => assert!(!choices.is_empty()),
+    }
+    self.params.push(param);
+    self
+  }
+
+  pub fn continuous(self, low: f64, high: f64) -> SearchSpace {
+    self.param(Param::Continuous { low, high })
+  }
+
+  pub fn log_scale(self, low: f64, high: f64) -> SearchSpace {
+    self.param(Param::LogScale { low, high })
+  }
+
+  pub fn integer(self, low: i64, high: i64) -> SearchSpace {
+    self.param(Param::Integer { low, high })
+  }
+
+  pub fn categorical<S: Into<String>>(self, choices: Vec<S>) -> SearchSpace {
+    let choices: Vec<String> = choices
+    .into_iter()
+    .map(|x| {x.into()})
+    .collect::<Vec<String>>();
+    self.param(Param::Categorical(choices))
+  }
+
+  pub fn dims(&self) -> u32 {
+    self.params.len() as u32
+  }
+
+  pub fn params(&self) -> &[Param] {
+    &self.params
+  }
+
+  /// Maps a normalized policy to parameter values.
+  pub fn decode(&self, policy: &[f64]) -> Vec<f64> {
+    self.params
+    .iter()
+    .zip(policy.iter())
+    .map(|(p, u)| {p.decode(*u)})
+    .collect::<Vec<f64>>()
+  }
+
+  /// [`decode`](SearchSpace::decode) into a reused buffer.
+  pub fn decode_into(&self, policy: &[f64], out: &mut Vec<f64>) {
+    out.clear();
+    out.extend(self.params.iter().zip(policy.iter()).map(|(p, u)| {p.decode(*u)}));
+  }
+
+  /// Maps parameter values to a normalized policy.
+  pub fn encode(&self, values: &[f64]) -> Vec<f64> {
+    self.params
+    .iter()
+    .zip(values.iter())
+    .map(|(p, x)| {p.encode(*x)})
+    .collect::<Vec<f64>>()
+  }
+}
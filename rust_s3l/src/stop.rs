@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::performance::Direction;
+
+/// Why a learning run ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StopReason {
+  MaxEvaluations,
+  TimeBudget,
+  TargetReached,
+  /// The best performance did not improve for the configured number of evaluations.
+  NoImprovement,
+  /// The best performance improved by less than epsilon over the configured window.
+  ImprovementBelowEpsilon
+}
+
+/// A set of stopping rules; a run stops as soon as any one of them fires.
+///
+/// With no rules set, a run never stops on its own.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StopCriteria {
+  max_evaluations: Option<usize>,
+  time_budget: Option<Duration>,
+  target_performance: Option<f64>,
+  patience: Option<usize>,
+  min_improvement: Option<(f64, usize)>
+}
+
+impl StopCriteria {
+  pub fn new() -> StopCriteria {
+    StopCriteria::default()
+  }
+
+  pub fn max_evaluations(mut self, max_evaluations: usize) -> StopCriteria {
+    self.max_evaluations = Some(max_evaluations);
+    self
+  }
+
+  pub fn time_budget(mut self, time_budget: Duration) -> StopCriteria {
+    self.time_budget = Some(time_budget);
+    self
+  }
+
+  pub fn target_performance(mut self, target_performance: f64) -> StopCriteria {
+    self.target_performance = Some(target_performance);
+    self
+  }
+
+  /// Stops after `patience` evaluations without a new best.
+  pub fn patience(mut self, patience: usize) -> StopCriteria {
+    self.patience = Some(patience);
+    self
+  }
+
+  /// Stops once the best performance gained less than `epsilon` over the last `window` evaluations.
+  pub fn min_improvement(mut self, epsilon: f64, window: usize) -> StopCriteria {
+    self.min_improvement = Some((epsilon, window));
+    self
+  }
+
+  pub fn is_empty(&self) -> bool {
+    *self == StopCriteria::default()
+  }
+
+  /// Checks the rules against the best-so-far performance after each evaluation of the run.
+  pub fn check(&self, best_history: &[f64], elapsed: Duration, direction: Direction) -> Option<StopReason> {
+    let evaluations: usize = best_history.len();
+    let best: f64 = *best_history.last()?;
+    if let Some(target_performance) = self.target_performance {
+      if !direction.is_better(target_performance, best) {
+        return Some(StopReason::TargetReached);
+      }
+    }
+    if let Some(max_evaluations) = self.max_evaluations {
+      if evaluations >= max_evaluations {
+        return Some(StopReason::MaxEvaluations);
+      }
+    }
+    if let Some(time_budget) = self.time_budget {
+      if elapsed >= time_budget {
+        return Some(StopReason::TimeBudget);
+      }
+    }
+    if let Some(patience) = self.patience {
+      let last_improvement: usize = (1..evaluations)
+      .rev()
+      .find(|x| {direction.is_better(best_history[*x], best_history[*x - 1])})
+      .unwrap_or(0);
+      if evaluations - 1 - last_improvement >= patience {
+        return Some(StopReason::NoImprovement);
+      }
+    }
+    if let Some((epsilon, window)) = self.min_improvement {
+      if evaluations > window && direction.sign() * (best - best_history[evaluations - 1 - window]) < epsilon {
+        return Some(StopReason::ImprovementBelowEpsilon);
+      }
+    }
+    None
+  }
+}
@@ -0,0 +1,85 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::S3LAgent;
+
+/// A proposed policy waiting for its performance to be reported.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Trial {
+  pub id: u64,
+  /// The normalized policy, as stored in the xi table.
+  pub policy: Vec<f64>,
+  /// The policy mapped to parameter values through the agent's search space.
+  pub params: Vec<f64>
+}
+
+impl<R: Rng> S3LAgent<R> {
+  /// Proposes a policy and tracks it as pending until it is told or abandoned.
+  pub fn ask(&mut self) -> Trial {
+    let policy: Vec<f64> = self.select_policy();
+    let id: u64 = self.next_trial_id;
+    self.next_trial_id += 1;
+    self.pending.insert(id, policy.clone());
+    Trial {
+      id,
+      params: self.decode(&policy),
+      policy,
+    }
+  }
+
+  /// Records the performance of a pending trial, in any order.
+  ///
+  /// A NaN performance records the trial as infeasible. Returns the trial's policy, or `None` if the id was never
+  /// asked, already told or abandoned.
+  pub fn tell(&mut self, id: u64, performance: f64) -> Option<Vec<f64>> {
+    let policy: Vec<f64> = self.pending.remove(&id)?;
+    self.update_xi_table(policy.clone(), performance);
+    Some(policy)
+  }
+
+  /// Records the objective vector of a pending multi-objective trial, in any order.
+  pub fn tell_objectives(&mut self, id: u64, objectives: Vec<f64>) -> Option<Vec<f64>> {
+    let policy: Vec<f64> = self.pending.remove(&id)?;
+    self.update_xi_table_objectives(policy.clone(), objectives);
+    Some(policy)
+  }
+
+  /// Records a pending trial as infeasible, in any order.
+  pub fn tell_infeasible(&mut self, id: u64) -> Option<Vec<f64>> {
+    let policy: Vec<f64> = self.pending.remove(&id)?;
+    self.update_xi_table_infeasible(policy.clone());
+    Some(policy)
+  }
+
+  /// Stops tracking a trial so a late result for it is ignored.
+  pub fn abandon(&mut self, id: u64) -> Option<Vec<f64>> {
+    self.pending.remove(&id)
+  }
+
+  /// Trials that have been asked but not yet told or abandoned, oldest first.
+  pub fn pending_trials(&self) -> Vec<Trial> {
+    self.pending
+    .iter()
+    .map(|(id, policy)| {Trial {id: *id, policy: policy.clone(), params: self.decode(policy)}})
+    .collect::<Vec<Trial>>()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::agent::S3LAgent;
+  use crate::trial::Trial;
+
+  #[test]
+  fn nan_performance_is_recorded_as_infeasible() {
+    let mut agentmodel: S3LAgent = S3LAgent::builder(2, 3.1).seed(1).build();
+    for i in 0..10 {
+      let trial: Trial = agentmodel.ask();
+      let performance: f64 = if i % 3 == 0 {f64::NAN} else {trial.params[0]};
+      agentmodel.tell(trial.id, performance);
+    }
+    assert_eq!(agentmodel.xi_table().infeasible().count(), 4);
+    assert!(agentmodel.xi_table().feasible().all(|x| {!x.performance.is_nan()}));
+    assert!(agentmodel.select_policy().iter().all(|x| {!x.is_nan()}));
+  }
+}
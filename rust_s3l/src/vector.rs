@@ -0,0 +1,48 @@
+//! Vector math on slices. The `_into` and `_in_place` variants write into caller-owned buffers and never allocate.
+
+/// The delta that moves `a` onto `b`, i.e. `b - a`.
+pub fn sub_vectors(a: &[f64], b: &[f64]) -> Vec<f64> {
+  let mut deltas: Vec<f64> = vec![0_f64; a.len()];
+  sub_vectors_into(a, b, &mut deltas);
+  deltas
+}
+
+/// Writes `b - a` into `out`.
+pub fn sub_vectors_into(a: &[f64], b: &[f64], out: &mut [f64]) {
+  for ((o, x), y) in out.iter_mut().zip(a.iter()).zip(b.iter()) {
+    *o = y - x;
+  }
+}
+
+pub fn sum_vectors(a: &[f64], b: &[f64]) -> Vec<f64> {
+  let mut sum_result: Vec<f64> = a.to_vec();
+  sum_vectors_in_place(&mut sum_result, b);
+  sum_result
+}
+
+/// Adds `b` to `a`.
+pub fn sum_vectors_in_place(a: &mut [f64], b: &[f64]) {
+  for (x, y) in a.iter_mut().zip(b.iter()) {
+    *x += y;
+  }
+}
+
+pub fn vector_scalar_multiply(m_vector: &[f64], m_scalar: f64) -> Vec<f64> {
+  let mut product: Vec<f64> = m_vector.to_vec();
+  vector_scalar_multiply_in_place(&mut product, m_scalar);
+  product
+}
+
+pub fn vector_scalar_multiply_in_place(m_vector: &mut [f64], m_scalar: f64) {
+  for x in m_vector.iter_mut() {
+    *x *= m_scalar;
+  }
+}
+
+/// Euclidean distance between `a` and `b`.
+pub fn get_l2_n_dist(a: &[f64], b: &[f64]) -> f64 {
+  a.iter()
+  .zip(b.iter())
+  .fold(0_f64, |a, x| {a + (x.0 - x.1).powi(2)})
+  .sqrt()
+}
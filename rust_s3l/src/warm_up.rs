@@ -0,0 +1,230 @@
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::agent::S3LAgent;
+
+/// How the initial design is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Sampling {
+  /// Independent uniform points.
+  #[default]
+  Uniform,
+  /// Exactly one of the `n` design points in each of `n` equal slices of every dimension. Points past the design
+  /// size are uniform.
+  LatinHypercube,
+  /// The Sobol low-discrepancy sequence, for up to [`MAX_SOBOL_DIMS`] dimensions.
+  Sobol,
+  /// The Halton low-discrepancy sequence, which degrades in high dimensions.
+  Halton
+}
+
+/// The random exploration that fills the xi table before S3L starts steering.
+///
+/// The warm-up lasts `base + per_dim * dims` evaluations; the default of three matches the original algorithm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WarmUp {
+  pub base: usize,
+  pub per_dim: usize,
+  pub sampling: Sampling
+}
+
+impl Default for WarmUp {
+  fn default() -> WarmUp {
+    WarmUp {
+      base: 3,
+      per_dim: 0,
+      sampling: Sampling::Uniform,
+    }
+  }
+}
+
+impl WarmUp {
+  pub fn size(&self, dims: u32) -> usize {
+    self.base + self.per_dim * (dims as usize)
+  }
+}
+
+/// Primitive polynomial degree, coefficient bits and initial direction numbers for Sobol dimensions 2 onwards,
+/// from Joe and Kuo.
+const SOBOL_PARAMETERS: [(u32, u32, [u32; 7]); 20] = [
+  (1, 0, [1, 0, 0, 0, 0, 0, 0]),
+  (2, 1, [1, 3, 0, 0, 0, 0, 0]),
+  (3, 1, [1, 3, 1, 0, 0, 0, 0]),
+  (3, 2, [1, 1, 1, 0, 0, 0, 0]),
+  (4, 1, [1, 1, 3, 3, 0, 0, 0]),
+  (4, 4, [1, 3, 5, 13, 0, 0, 0]),
+  (5, 2, [1, 1, 5, 5, 17, 0, 0]),
+  (5, 4, [1, 1, 5, 5, 5, 0, 0]),
+  (5, 7, [1, 1, 7, 11, 19, 0, 0]),
+  (5, 11, [1, 1, 5, 1, 1, 0, 0]),
+  (5, 13, [1, 1, 1, 3, 11, 0, 0]),
+  (5, 14, [1, 3, 5, 5, 31, 0, 0]),
+  (6, 1, [1, 3, 3, 9, 7, 49, 0]),
+  (6, 13, [1, 1, 1, 15, 21, 21, 0]),
+  (6, 16, [1, 3, 1, 13, 27, 49, 0]),
+  (6, 19, [1, 1, 1, 15, 7, 5, 0]),
+  (6, 22, [1, 3, 1, 15, 13, 25, 0]),
+  (6, 25, [1, 1, 5, 5, 19, 61, 0]),
+  (7, 1, [1, 3, 7, 11, 23, 15, 103]),
+  (7, 4, [1, 3, 7, 13, 13, 15, 69])
+];
+
+/// The most dimensions [`Sampling::Sobol`] supports.
+pub const MAX_SOBOL_DIMS: u32 = SOBOL_PARAMETERS.len() as u32 + 1;
+
+const SOBOL_BITS: usize = 32;
+
+/// The 32 direction numbers of Sobol dimension `dim`, counting from 0.
+fn sobol_directions(dim: usize) -> [u32; SOBOL_BITS] {
+  let mut v: [u32; SOBOL_BITS] = [0; SOBOL_BITS];
+  if dim == 0 {
+    for (k, x) in v.iter_mut().enumerate() {
+      *x = 1 << (SOBOL_BITS - 1 - k);
+    }
+    return v;
+  }
+  let (s, a, m): (u32, u32, [u32; 7]) = SOBOL_PARAMETERS[dim - 1];
+  let s: usize = s as usize;
+  for k in 0..SOBOL_BITS {
+    v[k] = if k < s {
+      m[k] << (SOBOL_BITS - 1 - k)
+    } else {
+      let mut x: u32 = v[k - s] ^ (v[k - s] >> s);
+      for i in 1..s {
+        if (a >> (s - 1 - i)) & 1 == 1 {
+          x ^= v[k - i];
+        }
+      }
+      x
+    };
+  }
+  v
+}
+
+/// Point `index` of the Sobol sequence, skipping the all-zero first point.
+fn sobol_point(index: u64, policy: &mut [f64]) {
+  let gray: u64 = (index + 1) ^ ((index + 1) >> 1);
+  for (dim, x) in policy.iter_mut().enumerate() {
+    let v: [u32; SOBOL_BITS] = sobol_directions(dim);
+    let bits: u32 = (0..SOBOL_BITS)
+    .filter(|k| {(gray >> k) & 1 == 1})
+    .fold(0, |a, k| {a ^ v[k]});
+    *x = bits as f64 / 2_f64.powi(SOBOL_BITS as i32);
+  }
+}
+
+fn nth_prime(n: usize) -> u64 {
+  let mut primes: Vec<u64> = Vec::new();
+  let mut candidate: u64 = 2;
+  while primes.len() <= n {
+    if primes.iter().take_while(|p| {**p * **p <= candidate}).all(|p| {!candidate.is_multiple_of(*p)}) {
+      primes.push(candidate);
+    }
+    candidate += 1;
+  }
+  primes[n]
+}
+
+/// Point `index` of the Halton sequence, skipping the all-zero first point.
+fn halton_point(index: u64, policy: &mut [f64]) {
+  for (dim, x) in policy.iter_mut().enumerate() {
+    let base: u64 = nth_prime(dim);
+    let mut i: u64 = index + 1;
+    let mut fraction: f64 = 1_f64;
+    *x = 0_f64;
+    while i > 0 {
+      fraction /= base as f64;
+      *x += fraction * ((i % base) as f64);
+      i /= base;
+    }
+  }
+}
+
+impl<R: Rng> S3LAgent<R> {
+  pub fn warm_up(&self) -> WarmUp {
+    self.warm_up
+  }
+
+  /// How many evaluations the current epoch needs before S3L starts steering, grown by IPOP restarts.
+  pub fn warm_up_size(&self) -> usize {
+    (self.warm_up.size(self.dims) as f64 * self.exploration_growth()).round() as usize
+  }
+
+  /// Whether the current epoch is still short of the warm-up size.
+  pub fn in_warm_up(&self) -> bool {
+    self.xi_table.epoch().len() < self.warm_up_size()
+  }
+
+  /// The next normalized point of the initial design.
+  ///
+  /// After a restart a Latin hypercube is drawn afresh, while the low-discrepancy sequences carry on where they
+  /// stopped.
+  pub fn design_policy(&mut self) -> Vec<f64> {
+    let index: u64 = self.design_drawn;
+    let epoch_index: u64 = index - self.design_start;
+    self.design_drawn += 1;
+    match self.warm_up.sampling {
+      Sampling::Uniform => self.random_policy(),
+      Sampling::LatinHypercube => {
+        if epoch_index == 0 {
+          self.latin_hypercube = self.latin_hypercube_design(self.warm_up_size());
+        }
+        match self.latin_hypercube.get(epoch_index as usize) {
+          Some(policy) => policy.clone(),
+          None => self.random_policy(),
+        }
+      }
+      Sampling::Sobol => {
+        let mut policy: Vec<f64> = vec![0_f64; self.dims as usize];
+        sobol_point(index, &mut policy);
+        policy
+      }
+      Sampling::Halton => {
+        let mut policy: Vec<f64> = vec![0_f64; self.dims as usize];
+        halton_point(index, &mut policy);
+        policy
+      }
+    }
+  }
+
+  fn latin_hypercube_design(&mut self, n: usize) -> Vec<Vec<f64>> {
+    let mut design: Vec<Vec<f64>> = vec![vec![0_f64; self.dims as usize]; n];
+    let mut strata: Vec<usize> = (0..n).collect::<Vec<usize>>();
+    for dim in 0..(self.dims as usize) {
+      strata.shuffle(&mut self.rng);
+      for (point, stratum) in design.iter_mut().zip(strata.iter()) {
+        point[dim] = ((*stratum as f64) + self.rng.gen::<f64>()) / (n as f64);
+      }
+    }
+    design
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use crate::agent::S3LAgent;
+  use crate::trial::Trial;
+  use crate::warm_up::{Sampling, WarmUp};
+
+  #[test]
+  fn every_entry_point_draws_the_initial_design() {
+    let warm_up: WarmUp = WarmUp { base: 4, per_dim: 0, sampling: Sampling::Sobol };
+    let mut designer: S3LAgent = S3LAgent::builder(2, 3.1).warm_up(warm_up).seed(1).build();
+    let design: Vec<Vec<f64>> = (0..4).map(|_x| {designer.design_policy()}).collect::<Vec<Vec<f64>>>();
+    let mut batched: S3LAgent = S3LAgent::builder(2, 3.1).warm_up(warm_up).seed(2).build();
+    let batch: Vec<Vec<f64>> = batched
+    .select_batch(4)
+    .into_iter()
+    .map(|x| {x.policy})
+    .collect::<Vec<Vec<f64>>>();
+    assert_eq!(batch, design);
+    let mut asked: S3LAgent = S3LAgent::builder(2, 3.1).warm_up(warm_up).seed(3).build();
+    for policy in design.iter() {
+      let trial: Trial = asked.ask();
+      assert_eq!(&trial.policy, policy);
+      asked.tell(trial.id, 1_f64);
+    }
+    assert!(!asked.in_warm_up());
+  }
+}
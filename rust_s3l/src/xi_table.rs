@@ -0,0 +1,711 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::kdtree::KdTree;
+use crate::metric::Metric;
+use crate::performance::Direction;
+
+/// One evaluated policy.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct XiEntry {
+  /// The normalized policy.
+  pub policy: Vec<f64>,
+  /// The mean over every sample of this policy.
+  pub performance: f64,
+  /// How many times the policy was evaluated.
+  #[serde(default = "one_sample")]
+  pub samples: u32,
+  /// Sample variance of the performance, 0 until a second sample arrives.
+  #[serde(default)]
+  pub variance: f64,
+  /// The objective vector of a multi-objective evaluation, scalarized into `performance`. Empty otherwise.
+  #[serde(default)]
+  pub objectives: Vec<f64>,
+  /// False when the policy violated a constraint; its performance is then meaningless and it is never ranked.
+  #[serde(default = "always_feasible")]
+  pub feasible: bool,
+  /// Position in the table, starting from 0.
+  pub step: u64,
+  /// When the entry was recorded.
+  pub timestamp: SystemTime,
+  pub metadata: BTreeMap<String, String>
+}
+
+fn one_sample() -> u32 {
+  1
+}
+
+fn always_feasible() -> bool {
+  true
+}
+
+impl XiEntry {
+  /// Folds another evaluation of the same policy into the mean and variance.
+  pub fn add_sample(&mut self, performance: f64) {
+    let n: f64 = self.samples as f64;
+    let delta: f64 = performance - self.performance;
+    self.performance += delta / (n + 1_f64);
+    let m2: f64 = self.variance * (n - 1_f64) + delta * (performance - self.performance);
+    self.samples += 1;
+    self.variance = m2 / n;
+  }
+}
+
+/// Every policy the agent has evaluated, in the order they were recorded.
+///
+/// Best, worst and rankings follow the table's [`Direction`] and use each entry's [`score`](XiTable::score).
+/// Entries with objective vectors are also kept in a non-dominated archive, and every policy in a spatial index
+/// for [`nearest`](XiTable::nearest).
+///
+/// A restart begins a new epoch: earlier entries stay in the table and still count for [`best`](XiTable::best), but
+/// only the current [`epoch`](XiTable::epoch) steers the agent.
+#[derive(Clone, Debug, PartialEq, Default, Serialize, Deserialize)]
+#[serde(from = "XiTableData")]
+pub struct XiTable {
+  entries: Vec<XiEntry>,
+  direction: Direction,
+  kappa: f64,
+  /// Steps of the non-dominated entries, ascending.
+  front: Vec<u64>,
+  pareto_ranking: bool,
+  /// Step of the first entry of the current epoch.
+  epoch_start: usize,
+  /// Steps of the current epoch's feasible entries that no other entry of the epoch dominates, ascending.
+  #[serde(skip)]
+  epoch_front: Vec<u64>,
+  /// Steps of the current epoch's feasible entries from worst to best score, ties in step order.
+  #[serde(skip)]
+  epoch_order: Vec<u64>,
+  /// Steps of the current epoch's infeasible entries, ascending.
+  #[serde(skip)]
+  epoch_infeasible: Vec<u64>,
+  /// Step of the best feasible entry, the latest one on ties.
+  #[serde(skip)]
+  best: Option<u64>,
+  #[serde(skip)]
+  noise_variance: f64,
+  #[serde(skip)]
+  index: KdTree
+}
+
+/// The saved fields of [`XiTable`]; the spatial index, the epoch's front and the rankings are rebuilt on load.
+#[derive(Deserialize)]
+struct XiTableData {
+  entries: Vec<XiEntry>,
+  #[serde(default)]
+  direction: Direction,
+  #[serde(default)]
+  kappa: f64,
+  #[serde(default)]
+  front: Vec<u64>,
+  #[serde(default)]
+  pareto_ranking: bool,
+  #[serde(default)]
+  epoch_start: usize
+}
+
+impl From<XiTableData> for XiTable {
+  fn from(data: XiTableData) -> XiTable {
+    let mut index: KdTree = KdTree::new();
+    for entry in data.entries.iter() {
+      index.insert(&entry.policy);
+    }
+    let mut table: XiTable = XiTable {
+      entries: data.entries,
+      direction: data.direction,
+      kappa: data.kappa,
+      front: data.front,
+      pareto_ranking: data.pareto_ranking,
+      epoch_start: data.epoch_start,
+      epoch_front: Vec::new(),
+      epoch_order: Vec::new(),
+      epoch_infeasible: Vec::new(),
+      best: None,
+      noise_variance: 0_f64,
+      index,
+    };
+    for step in table.epoch_start..table.entries.len() {
+      table.update_epoch_front(step as u64);
+    }
+    table.rebuild_rankings();
+    table
+  }
+}
+
+impl XiTable {
+  pub fn new() -> XiTable {
+    XiTable::with_direction(Direction::Maximize)
+  }
+
+  pub fn with_direction(direction: Direction) -> XiTable {
+    XiTable {
+      entries: Vec::new(),
+      direction,
+      kappa: 0_f64,
+      front: Vec::new(),
+      pareto_ranking: false,
+      epoch_start: 0,
+      epoch_front: Vec::new(),
+      epoch_order: Vec::new(),
+      epoch_infeasible: Vec::new(),
+      best: None,
+      noise_variance: 0_f64,
+      index: KdTree::new(),
+    }
+  }
+
+  /// Standard errors a mean is penalized by when scored. 0 scores every entry by its plain mean.
+  pub fn kappa(&self) -> f64 {
+    self.kappa
+  }
+
+  pub fn set_kappa(&mut self, kappa: f64) {
+    self.kappa = kappa;
+    self.rebuild_rankings();
+  }
+
+  pub fn direction(&self) -> Direction {
+    self.direction
+  }
+
+  /// Whether [`ranked`](XiTable::ranked) orders by non-dominated rank before score.
+  pub fn pareto_ranking(&self) -> bool {
+    self.pareto_ranking
+  }
+
+  pub fn set_pareto_ranking(&mut self, pareto_ranking: bool) {
+    self.pareto_ranking = pareto_ranking;
+  }
+
+  pub fn push(&mut self, policy: Vec<f64>, performance: f64, metadata: BTreeMap<String, String>) -> &XiEntry {
+    let step: u64 = self.entries.len() as u64;
+    self.push_entry(policy, performance, true, metadata);
+    self.update_epoch_front(step);
+    &self.entries[step as usize]
+  }
+
+  /// Records a multi-objective evaluation, updating the non-dominated archive.
+  pub fn push_objectives(&mut self, policy: Vec<f64>, objectives: Vec<f64>, performance: f64, metadata: BTreeMap<String, String>) -> &XiEntry {
+    let step: u64 = self.entries.len() as u64;
+    let direction: Direction = self.direction;
+    let dominated: bool = self.front
+    .iter()
+    .any(|x| {direction.dominates(&self.entries[*x as usize].objectives, &objectives)});
+    if !dominated {
+      let entries: &[XiEntry] = &self.entries;
+      self.front.retain(|x| {!direction.dominates(&objectives, &entries[*x as usize].objectives)});
+      self.front.push(step);
+    }
+    self.push_entry(policy, performance, true, metadata);
+    self.entries[step as usize].objectives = objectives;
+    self.update_epoch_front(step);
+    &self.entries[step as usize]
+  }
+
+  /// Adds the feasible entry at `step` to the epoch's front unless it is dominated, dropping what it dominates.
+  fn update_epoch_front(&mut self, step: u64) {
+    let entries: &[XiEntry] = &self.entries;
+    let direction: Direction = self.direction;
+    let objectives: &[f64] = &entries[step as usize].objectives;
+    if !entries[step as usize].feasible || self.epoch_front.iter().any(|x| {direction.dominates(&entries[*x as usize].objectives, objectives)}) {
+      return;
+    }
+    self.epoch_front.retain(|x| {!direction.dominates(objectives, &entries[*x as usize].objectives)});
+    self.epoch_front.push(step);
+  }
+
+  /// Records a policy that violated a constraint.
+  pub fn push_infeasible(&mut self, policy: Vec<f64>, metadata: BTreeMap<String, String>) -> &XiEntry {
+    self.push_entry(policy, 0_f64, false, metadata)
+  }
+
+  fn push_entry(&mut self, policy: Vec<f64>, performance: f64, feasible: bool, metadata: BTreeMap<String, String>) -> &XiEntry {
+    let new_entry: XiEntry = XiEntry {
+      policy,
+      performance,
+      samples: 1,
+      variance: 0_f64,
+      objectives: Vec::new(),
+      feasible,
+      step: self.entries.len() as u64,
+      timestamp: SystemTime::now(),
+      metadata,
+    };
+    self.index.insert(&new_entry.policy);
+    self.entries.push(new_entry);
+    let step: u64 = (self.entries.len() - 1) as u64;
+    if feasible {
+      self.insert_ranked(step);
+    } else {
+      self.epoch_infeasible.push(step);
+    }
+    &self.entries[step as usize]
+  }
+
+  /// Orders the feasible entries at `a` and `b` by score, the earlier one first on ties.
+  fn compare_steps(&self, a: u64, b: u64, noise_variance: f64) -> Ordering {
+    let a_score: f64 = self.score_with(&self.entries[a as usize], noise_variance);
+    let b_score: f64 = self.score_with(&self.entries[b as usize], noise_variance);
+    self.direction
+    .compare(a_score, b_score)
+    .then(a.cmp(&b))
+  }
+
+  /// Places a new feasible entry in the epoch's order and checks it against the best. A single sample leaves the
+  /// pooled noise variance, and so every other score, unchanged.
+  fn insert_ranked(&mut self, step: u64) {
+    let noise_variance: f64 = self.pooled_variance();
+    let position: usize = self.epoch_order
+    .partition_point(|x| {self.compare_steps(*x, step, noise_variance) == Ordering::Less});
+    self.epoch_order.insert(position, step);
+    if self.best.is_none_or(|x| {self.compare_steps(x, step, noise_variance) == Ordering::Less}) {
+      self.best = Some(step);
+    }
+  }
+
+  /// Recomputes the pooled noise variance, the epoch's order and the best entry after scores changed.
+  fn rebuild_rankings(&mut self) {
+    let (sum, dof): (f64, f64) = self.entries
+    .iter()
+    .filter(|x| {x.samples > 1})
+    .fold((0_f64, 0_f64), |a, x| {(a.0 + x.variance * ((x.samples - 1) as f64), a.1 + ((x.samples - 1) as f64))});
+    self.noise_variance = if dof == 0_f64 {0_f64} else {sum / dof};
+    let noise_variance: f64 = self.pooled_variance();
+    let (feasible, infeasible): (Vec<u64>, Vec<u64>) = (self.epoch_start as u64..self.entries.len() as u64)
+    .partition(|x| {self.entries[*x as usize].feasible});
+    let mut order: Vec<u64> = feasible;
+    order.sort_by(|a, b| {self.compare_steps(*a, *b, noise_variance)});
+    self.epoch_order = order;
+    self.epoch_infeasible = infeasible;
+    self.best = (0..self.entries.len() as u64)
+    .filter(|x| {self.entries[*x as usize].feasible})
+    .max_by(|a, b| {self.compare_steps(*a, *b, noise_variance)});
+  }
+
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  pub fn entries(&self) -> &[XiEntry] {
+    &self.entries
+  }
+
+  pub fn iter(&self) -> std::slice::Iter<'_, XiEntry> {
+    self.entries.iter()
+  }
+
+  pub fn feasible(&self) -> impl Iterator<Item = &XiEntry> {
+    self.entries
+    .iter()
+    .filter(|x| {x.feasible})
+  }
+
+  pub fn infeasible(&self) -> impl Iterator<Item = &XiEntry> {
+    self.entries
+    .iter()
+    .filter(|x| {!x.feasible})
+  }
+
+  /// The entry whose policy is closest to `target` under `metric` among those `accept` lets through, with that
+  /// distance. Uses the spatial index, so with a metric that bounds per-axis distances only a fraction of the table
+  /// is visited.
+  pub fn nearest<F: Fn(&XiEntry) -> bool>(&self, target: &[f64], metric: &dyn Metric, accept: F) -> Option<(&XiEntry, f64)> {
+    self.nearest_with(target, metric, accept, &mut Vec::new())
+  }
+
+  /// [`nearest`](XiTable::nearest) with caller-owned working space for the tree walk.
+  pub(crate) fn nearest_with<F: Fn(&XiEntry) -> bool>(&self, target: &[f64], metric: &dyn Metric, accept: F, stack: &mut Vec<(usize, f64)>) -> Option<(&XiEntry, f64)> {
+    self.index
+    .nearest(target, metric, |x| {accept(&self.entries[x])}, stack)
+    .map(|x| {(&self.entries[x.0], x.1)})
+  }
+
+  /// Records another evaluation of the policy at `step`.
+  pub fn add_sample(&mut self, step: u64, performance: f64) -> Option<&XiEntry> {
+    self.entries.get_mut(step as usize)?.add_sample(performance);
+    self.rebuild_rankings();
+    Some(&self.entries[step as usize])
+  }
+
+  /// Noise variance pooled over every entry with at least two samples, or 0 if there are none.
+  pub fn noise_variance(&self) -> f64 {
+    self.noise_variance
+  }
+
+  fn score_with(&self, entry: &XiEntry, noise_variance: f64) -> f64 {
+    if self.kappa == 0_f64 {
+      return entry.performance;
+    }
+    let variance: f64 = if entry.samples > 1 {entry.variance} else {noise_variance};
+    let standard_error: f64 = (variance / (entry.samples as f64)).sqrt();
+    entry.performance - self.direction.sign() * self.kappa * standard_error
+  }
+
+  fn pooled_variance(&self) -> f64 {
+    if self.kappa == 0_f64 {0_f64} else {self.noise_variance()}
+  }
+
+  /// [`score`](XiTable::score) with the pooled noise variance computed once, for scoring many entries.
+  pub(crate) fn scorer(&self) -> impl Fn(&XiEntry) -> Option<f64> + '_ {
+    let noise_variance: f64 = self.pooled_variance();
+    move |x| {if x.feasible {Some(self.score_with(x, noise_variance))} else {None}}
+  }
+
+  /// The noise-aware performance used for ranking: the mean made `kappa` standard errors worse, a lower confidence
+  /// bound when maximizing. Single-sample entries use the pooled noise variance. `None` for infeasible entries.
+  pub fn score(&self, entry: &XiEntry) -> Option<f64> {
+    if !entry.feasible {
+      return None;
+    }
+    Some(self.score_with(entry, self.pooled_variance()))
+  }
+
+  /// The score of every entry, in table order.
+  pub fn scores(&self) -> Vec<Option<f64>> {
+    let noise_variance: f64 = self.pooled_variance();
+    self.entries
+    .iter()
+    .map(|x| {if x.feasible {Some(self.score_with(x, noise_variance))} else {None}})
+    .collect::<Vec<Option<f64>>>()
+  }
+
+  /// The most recently recorded entry.
+  pub fn last(&self) -> Option<&XiEntry> {
+    self.entries.last()
+  }
+
+  /// The best feasible entry.
+  pub fn best(&self) -> Option<&XiEntry> {
+    self.best.map(|x| {&self.entries[x as usize]})
+  }
+
+  /// The worst feasible entry.
+  pub fn worst(&self) -> Option<&XiEntry> {
+    let noise_variance: f64 = self.pooled_variance();
+    self.entries
+    .iter()
+    .filter(|x| {x.feasible})
+    .min_by(|a, b| {self.direction.compare(self.score_with(a, noise_variance), self.score_with(b, noise_variance))})
+  }
+
+  pub fn epoch_start(&self) -> usize {
+    self.epoch_start
+  }
+
+  /// Begins a new epoch after the last recorded entry.
+  pub(crate) fn start_epoch(&mut self) {
+    self.epoch_start = self.entries.len();
+    self.epoch_front.clear();
+    self.epoch_order.clear();
+    self.epoch_infeasible.clear();
+  }
+
+  /// The entries recorded since the last restart.
+  pub fn epoch(&self) -> &[XiEntry] {
+    &self.entries[self.epoch_start..]
+  }
+
+  /// The best feasible entry of the current epoch.
+  pub fn epoch_best(&self) -> Option<&XiEntry> {
+    self.epoch_order.last().map(|x| {&self.entries[*x as usize]})
+  }
+
+  /// The worst feasible entry of the current epoch.
+  pub fn epoch_worst(&self) -> Option<&XiEntry> {
+    self.epoch_order.first().map(|x| {&self.entries[*x as usize]})
+  }
+
+  /// The current epoch's feasible entries from worst to best score, the earlier one first on ties.
+  pub(crate) fn epoch_order(&self) -> &[u64] {
+    &self.epoch_order
+  }
+
+  /// The current epoch's infeasible entries.
+  pub(crate) fn epoch_infeasible(&self) -> &[u64] {
+    &self.epoch_infeasible
+  }
+
+  /// The non-dominated entries, in the order they were recorded.
+  pub fn pareto_front(&self) -> Vec<&XiEntry> {
+    self.front
+    .iter()
+    .map(|x| {&self.entries[*x as usize]})
+    .collect::<Vec<&XiEntry>>()
+  }
+
+  /// The non-dominated sorting rank of every entry, 0 being the Pareto front. `None` for infeasible entries;
+  /// entries without objective vectors dominate nothing and are never dominated.
+  pub fn pareto_ranks(&self) -> Vec<Option<usize>> {
+    let mut ranks: Vec<Option<usize>> = vec![None; self.entries.len()];
+    let mut remaining: Vec<usize> = (0..self.entries.len())
+    .filter(|x| {self.entries[*x].feasible})
+    .collect::<Vec<usize>>();
+    let mut rank: usize = 0;
+    while !remaining.is_empty() {
+      let (layer, rest): (Vec<usize>, Vec<usize>) = remaining
+      .iter()
+      .partition(|a| {!remaining.iter().any(|b| {self.direction.dominates(&self.entries[*b].objectives, &self.entries[**a].objectives)})});
+      for i in layer.iter() {
+        ranks[*i] = Some(rank);
+      }
+      remaining = rest;
+      rank += 1;
+    }
+    ranks
+  }
+
+  /// Crowding distance of every entry within its Pareto rank: the summed normalized gap between its neighbours
+  /// along each objective, infinite at the ends of a rank.
+  fn crowding_distances(&self, ranks: &[Option<usize>]) -> Vec<f64> {
+    let mut distances: Vec<f64> = vec![0_f64; self.entries.len()];
+    let rank_count: usize = ranks.iter().flatten().max().map_or(0, |x| {x + 1});
+    for rank in 0..rank_count {
+      let layer: Vec<usize> = (0..self.entries.len())
+      .filter(|x| {ranks[*x] == Some(rank)})
+      .collect::<Vec<usize>>();
+      for (i, distance) in layer.iter().zip(self.layer_crowding(&layer)) {
+        distances[*i] = distance;
+      }
+    }
+    distances
+  }
+
+  /// The crowding distance of every entry of `layer`, in the same order. Entries without an objective vector, or
+  /// with fewer objectives than the rest, take no part and keep a distance of 0.
+  fn layer_crowding(&self, layer: &[usize]) -> Vec<f64> {
+    let mut distances: Vec<f64> = vec![0_f64; layer.len()];
+    let objective_count: usize = layer.iter().map(|x| {self.entries[*x].objectives.len()}).max().unwrap_or(0);
+    let mut sorted: Vec<usize> = (0..layer.len())
+    .filter(|x| {self.entries[layer[*x]].objectives.len() == objective_count})
+    .collect::<Vec<usize>>();
+    if sorted.is_empty() {
+      return distances;
+    }
+    let objective = |i: usize, m: usize| -> f64 {self.entries[layer[i]].objectives[m]};
+    for m in 0..objective_count {
+      sorted.sort_by(|a, b| {objective(*a, m).total_cmp(&objective(*b, m))});
+      let low: f64 = objective(sorted[0], m);
+      let high: f64 = objective(sorted[sorted.len() - 1], m);
+      distances[sorted[0]] = f64::INFINITY;
+      distances[sorted[sorted.len() - 1]] = f64::INFINITY;
+      if high == low {
+        continue;
+      }
+      for i in 1..(sorted.len().saturating_sub(1)) {
+        distances[sorted[i]] += (objective(sorted[i + 1], m) - objective(sorted[i - 1], m)) / (high - low);
+      }
+    }
+    distances
+  }
+
+  /// Up to `k` entries of `layer` in [`ranked`](XiTable::ranked) order within that layer: by crowding distance,
+  /// then by score.
+  fn best_of_layer(&self, layer: &[usize], k: usize) -> Vec<usize> {
+    let score = self.scorer();
+    let distances: Vec<f64> = self.layer_crowding(layer);
+    let mut order: Vec<usize> = (0..layer.len()).collect::<Vec<usize>>();
+    order.sort_by(|a, b| {
+      distances[*b]
+      .total_cmp(&distances[*a])
+      .then(self.direction.compare(score(&self.entries[layer[*b]]).unwrap(), score(&self.entries[layer[*a]]).unwrap()))
+    });
+    order
+    .iter()
+    .take(k)
+    .map(|x| {layer[*x]})
+    .collect::<Vec<usize>>()
+  }
+
+  /// All feasible entries from best to worst. When [`pareto_ranking`](XiTable::pareto_ranking) is set they are
+  /// ordered by Pareto rank and crowding distance instead, with score only breaking ties.
+  pub fn ranked(&self) -> Vec<&XiEntry> {
+    let scores: Vec<Option<f64>> = self.scores();
+    let (ranks, distances): (Vec<Option<usize>>, Vec<f64>) = if self.pareto_ranking {
+      let ranks: Vec<Option<usize>> = self.pareto_ranks();
+      let distances: Vec<f64> = self.crowding_distances(&ranks);
+      (ranks, distances)
+    } else {
+      (vec![Some(0); self.entries.len()], vec![0_f64; self.entries.len()])
+    };
+    let mut ranking: Vec<usize> = (0..self.entries.len())
+    .filter(|x| {self.entries[*x].feasible})
+    .collect::<Vec<usize>>();
+    ranking.sort_by(|a, b| {
+      ranks[*a]
+      .cmp(&ranks[*b])
+      .then(distances[*b].total_cmp(&distances[*a]))
+      .then(self.direction.compare(scores[*b].unwrap(), scores[*a].unwrap()))
+    });
+    ranking
+    .iter()
+    .map(|x| {&self.entries[*x]})
+    .collect::<Vec<&XiEntry>>()
+  }
+
+  /// The first two entries of the current epoch in [`ranked`](XiTable::ranked) order, with Pareto ranks taken
+  /// within the epoch. Only allocates under Pareto ranking, where it works from the epoch's front and only sorts
+  /// the next rank when the front holds a single entry.
+  pub(crate) fn top_two(&self) -> (Option<&XiEntry>, Option<&XiEntry>) {
+    if self.pareto_ranking {
+      let front: Vec<usize> = self.epoch_front.iter().map(|x| {*x as usize}).collect::<Vec<usize>>();
+      let mut top: Vec<usize> = self.best_of_layer(&front, 2);
+      if top.len() == 1 {
+        top.extend(self.best_of_layer(&self.epoch_second_rank(), 1));
+      }
+      return (top.first().map(|x| {&self.entries[*x]}), top.get(1).map(|x| {&self.entries[*x]}));
+    }
+    // `ranked` keeps the earlier entry first on ties, while the epoch's order puts it last: take the earliest two of
+    // the best score, or the earliest of the next best.
+    let score = self.scorer();
+    let order: &[u64] = &self.epoch_order;
+    let score_at = |i: usize| -> f64 {score(&self.entries[order[i] as usize]).unwrap()};
+    let tied_start = |end: usize| -> usize {
+      let top: f64 = score_at(end - 1);
+      order[..end].partition_point(|x| {self.direction.is_better(top, score(&self.entries[*x as usize]).unwrap())})
+    };
+    if order.is_empty() {
+      return (None, None);
+    }
+    let first: usize = tied_start(order.len());
+    let second: Option<usize> = if first + 1 < order.len() {
+      Some(first + 1)
+    } else if first > 0 {
+      Some(tied_start(first))
+    } else {
+      None
+    };
+    (Some(&self.entries[order[first] as usize]), second.map(|x| {&self.entries[order[x] as usize]}))
+  }
+
+  /// The feasible entries of the epoch that only its front dominates, ascending.
+  fn epoch_second_rank(&self) -> Vec<usize> {
+    let mut layer: Vec<usize> = Vec::new();
+    for step in self.epoch_start..self.entries.len() {
+      let objectives: &[f64] = &self.entries[step].objectives;
+      if !self.entries[step].feasible || self.epoch_front.contains(&(step as u64)) || layer.iter().any(|x| {self.direction.dominates(&self.entries[*x].objectives, objectives)}) {
+        continue;
+      }
+      layer.retain(|x| {!self.direction.dominates(objectives, &self.entries[*x].objectives)});
+      layer.push(step);
+    }
+    layer
+  }
+
+  /// The `k` best feasible entries, best first.
+  pub fn top_k(&self, k: usize) -> Vec<&XiEntry> {
+    let mut ranking: Vec<&XiEntry> = self.ranked();
+    ranking.truncate(k);
+    ranking
+  }
+
+  /// Feasible entries with performance at or above `threshold`.
+  pub fn at_or_above(&self, threshold: f64) -> impl Iterator<Item = &XiEntry> {
+    self.feasible()
+    .filter(move |x| {x.performance >= threshold})
+  }
+
+  /// Feasible entries with performance strictly below `threshold`.
+  pub fn below(&self, threshold: f64) -> impl Iterator<Item = &XiEntry> {
+    self.feasible()
+    .filter(move |x| {x.performance < threshold})
+  }
+}
+
+impl<'a> IntoIterator for &'a XiTable {
+  type Item = &'a XiEntry;
+  type IntoIter = std::slice::Iter<'a, XiEntry>;
+
+  fn into_iter(self) -> std::slice::Iter<'a, XiEntry> {
+    self.entries.iter()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::collections::BTreeMap;
+
+  use rand::rngs::StdRng;
+  use rand::{Rng, SeedableRng};
+
+  use crate::performance::Direction;
+  use crate::xi_table::{XiEntry, XiTable};
+
+  fn steps(entries: &[&XiEntry]) -> Vec<u64> {
+    entries.iter().map(|x| {x.step}).collect::<Vec<u64>>()
+  }
+
+  /// Checks `top_two` against `ranked` after every push, with scalar and infeasible entries mixed in.
+  fn check_top_two(objectives: fn(&[f64]) -> Vec<f64>) {
+    let mut rng: StdRng = StdRng::seed_from_u64(3);
+    let mut xi_table: XiTable = XiTable::new();
+    xi_table.set_pareto_ranking(true);
+    for i in 0..200 {
+      let policy: Vec<f64> = vec![rng.gen::<f64>(), rng.gen::<f64>()];
+      match i % 10 {
+        3 => {xi_table.push(policy, 0.5, BTreeMap::new());}
+        5 => {xi_table.push_infeasible(policy, BTreeMap::new());}
+        _ => {
+          let objectives: Vec<f64> = objectives(&policy);
+          xi_table.push_objectives(policy, objectives, 0_f64, BTreeMap::new());
+        }
+      }
+      let (first, second): (Option<&XiEntry>, Option<&XiEntry>) = xi_table.top_two();
+      let top: Vec<&XiEntry> = first.into_iter().chain(second).collect::<Vec<&XiEntry>>();
+      assert_eq!(steps(&top), steps(&xi_table.top_k(2)));
+    }
+  }
+
+  /// Checks the cached rankings against scans of the table through pushes, re-samples, ties and a restart.
+  fn check_rankings(kappa: f64, direction: Direction) {
+    let mut rng: StdRng = StdRng::seed_from_u64(4);
+    let mut xi_table: XiTable = XiTable::with_direction(direction);
+    xi_table.set_kappa(kappa);
+    for i in 0..300 {
+      let policy: Vec<f64> = vec![rng.gen::<f64>(), rng.gen::<f64>()];
+      match i % 7 {
+        2 => {xi_table.push_infeasible(policy, BTreeMap::new());}
+        5 if i > 20 => {
+          let step: u64 = rng.gen_range(0, xi_table.len() as u64);
+          if xi_table.entries()[step as usize].feasible {
+            xi_table.add_sample(step, (rng.gen::<f64>() * 4_f64).round());
+          }
+        }
+        _ => {xi_table.push(policy, (rng.gen::<f64>() * 4_f64).round(), BTreeMap::new());}
+      }
+      if i == 150 {
+        xi_table.start_epoch();
+      }
+      let score = xi_table.scorer();
+      let compare = |a: &&XiEntry, b: &&XiEntry| {direction.compare(score(a).unwrap(), score(b).unwrap())};
+      let epoch: Vec<&XiEntry> = xi_table.epoch().iter().filter(|x| {x.feasible}).collect::<Vec<&XiEntry>>();
+      let mut ranked: Vec<&XiEntry> = epoch.clone();
+      ranked.sort_by(|a, b| {compare(b, a)});
+      let (first, second): (Option<&XiEntry>, Option<&XiEntry>) = xi_table.top_two();
+      assert_eq!(xi_table.best().map(|x| {x.step}), xi_table.feasible().max_by(compare).map(|x| {x.step}));
+      assert_eq!(xi_table.epoch_best().map(|x| {x.step}), epoch.iter().copied().max_by(compare).map(|x| {x.step}));
+      assert_eq!(xi_table.epoch_worst().map(|x| {x.step}), epoch.iter().copied().min_by(compare).map(|x| {x.step}));
+      let top: Vec<&XiEntry> = first.into_iter().chain(second).collect::<Vec<&XiEntry>>();
+      assert_eq!(steps(&top), steps(&ranked[..ranked.len().min(2)]));
+    }
+  }
+
+  #[test]
+  fn cached_rankings_match_a_scan() {
+    check_rankings(0_f64, Direction::Maximize);
+    check_rankings(1_f64, Direction::Maximize);
+    check_rankings(1_f64, Direction::Minimize);
+  }
+
+  #[test]
+  fn top_two_matches_pareto_ranking() {
+    check_top_two(|x| {vec![x[0], 1_f64 - x[0] - x[1]]});
+    check_top_two(|x| {vec![x[0], x[0] + x[1]]});
+  }
+}